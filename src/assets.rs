@@ -22,6 +22,7 @@ pub struct Sounds {
     pub hurt: geng::Sound,
     pub deliver: geng::Sound,
     pub death: geng::Sound,
+    pub boost: geng::Sound,
 }
 
 #[derive(geng::Load)]
@@ -43,6 +44,8 @@ pub struct Shaders {
 #[derive(geng::Load)]
 pub struct Sprites {
     pub props: Props,
+    #[load(path = "props/bricks.png")]
+    pub background: Texture,
     pub car: Texture,
     pub wall: Texture,
     pub bike: Texture,
@@ -52,15 +55,41 @@ pub struct Sprites {
     pub arrow: ugli::Texture,
 }
 
-#[derive(geng::Load)]
+/// Directory of the props relative to the assets.
+pub const PROPS_DIR: &str = "sprites/props";
+const PROPS_MANIFEST: &str = "props.json";
+
+/// Props described by the `props.json` manifest.
 pub struct Props {
-    pub road: Texture,
-    pub road_v: Texture,
-    pub road_ur: Texture,
-    pub road_ul: Texture,
-    pub road_rd: Texture,
-    pub road_dl: Texture,
-    pub bricks: Texture,
+    /// Configs grouped by category in the order of the manifest.
+    configs: Vec<PropConfig>,
+    textures: HashMap<model::PropType, Texture>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropConfig {
+    pub name: model::PropType,
+    /// Path to the texture relative to the manifest.
+    pub texture: String,
+    /// Path to the normal map relative to the manifest.
+    #[serde(default)]
+    pub normal: Option<String>,
+    /// Size of the prop placed with a single click.
+    #[serde(default = "default_prop_size")]
+    pub size: vec2<model::Coord>,
+    /// Whether the prop blocks the light like an obstacle.
+    #[serde(default)]
+    pub shadows: bool,
+    #[serde(default = "default_prop_category")]
+    pub category: String,
+}
+
+fn default_prop_size() -> vec2<model::Coord> {
+    vec2(model::Coord::ONE, model::Coord::ONE)
+}
+
+fn default_prop_category() -> String {
+    "Other".to_owned()
 }
 
 fn looped(sound: &mut geng::Sound) {
@@ -73,17 +102,80 @@ fn pixel(texture: &mut ugli::Texture) {
 
 impl Props {
     pub fn get(&self, prop: &model::PropType) -> Option<&Texture> {
-        match prop.as_str() {
-            "road" => Some(&self.road),
-            "road_v" => Some(&self.road_v),
-            "road_ur" => Some(&self.road_ur),
-            "road_ul" => Some(&self.road_ul),
-            "road_rd" => Some(&self.road_rd),
-            "road_dl" => Some(&self.road_dl),
-            "bricks" => Some(&self.bricks),
-            _ => None,
+        self.textures.get(prop)
+    }
+
+    pub fn config(&self, prop: &model::PropType) -> Option<&PropConfig> {
+        self.configs.iter().find(|config| &config.name == prop)
+    }
+
+    pub fn configs(&self) -> &[PropConfig] {
+        &self.configs
+    }
+
+    pub fn contains(&self, prop: &model::PropType) -> bool {
+        self.config(prop).is_some()
+    }
+
+    /// Reads the manifest in the directory without loading the textures,
+    /// for the tools that run without a window.
+    pub fn load_manifest(path: &std::path::Path) -> anyhow::Result<Self> {
+        let reader = std::io::BufReader::new(std::fs::File::open(path.join(PROPS_MANIFEST))?);
+        let mut configs: Vec<PropConfig> = serde_json::from_reader(reader)?;
+        group_by_category(&mut configs);
+        Ok(Self {
+            configs,
+            textures: HashMap::new(),
+        })
+    }
+}
+
+/// Stable sort keeps the manifest order inside the categories.
+fn group_by_category(configs: &mut [PropConfig]) {
+    let mut categories: Vec<String> = Vec::new();
+    for config in configs.iter() {
+        if !categories.contains(&config.category) {
+            categories.push(config.category.clone());
+        }
+    }
+    configs.sort_by_key(|config| {
+        categories
+            .iter()
+            .position(|category| *category == config.category)
+    });
+}
+
+impl geng::Load for Props {
+    fn load(manager: &geng::Manager, path: &std::path::Path) -> geng::asset::Future<Self> {
+        let path = path.to_owned();
+        let manager = manager.clone();
+        async move {
+            let mut configs: Vec<PropConfig> = file::load_json(path.join(PROPS_MANIFEST)).await?;
+            group_by_category(&mut configs);
+
+            let mut textures = HashMap::new();
+            for config in &configs {
+                let texture = load_pixel_texture(&manager, &path.join(&config.texture)).await?;
+                let normal = match &config.normal {
+                    Some(normal) => Some(load_pixel_texture(&manager, &path.join(normal)).await?),
+                    None => None,
+                };
+                textures.insert(config.name.clone(), Texture { texture, normal });
+            }
+            Ok(Self { configs, textures })
         }
+        .boxed_local()
     }
+    const DEFAULT_EXT: Option<&'static str> = None;
+}
+
+async fn load_pixel_texture(
+    manager: &geng::Manager,
+    path: &std::path::Path,
+) -> anyhow::Result<Rc<ugli::Texture>> {
+    let mut texture = ugli::Texture::load(manager, path).await?;
+    texture.set_filter(ugli::Filter::Nearest);
+    Ok(Rc::new(texture))
 }
 
 impl Texture {
@@ -100,18 +192,11 @@ impl geng::Load for Texture {
         let path = path.to_owned();
         let manager = manager.clone();
         async move {
-            let mut texture = ugli::Texture::load(&manager, &path).await?;
-            texture.set_filter(ugli::Filter::Nearest);
-            let texture = Rc::new(texture);
+            let texture = load_pixel_texture(&manager, &path).await?;
             let name = path.file_stem().unwrap().to_str().unwrap();
             let normal_path = path.with_file_name(format!("{name}_normal.png"));
             let normal = util::report_warn(
-                async {
-                    let mut texture = ugli::Texture::load(&manager, &normal_path).await?;
-                    texture.set_filter(ugli::Filter::Nearest);
-                    Result::<_, anyhow::Error>::Ok(Rc::new(texture))
-                }
-                .await,
+                load_pixel_texture(&manager, &normal_path).await,
                 format!("Failed to load normals for {name}"),
             )
             .ok();
@@ -0,0 +1,120 @@
+use super::*;
+
+use std::path::Path;
+
+/// Interval between the autosaves to the recovery file.
+pub(super) const AUTOSAVE_INTERVAL: f32 = 60.0;
+/// Number of the backups kept for each level.
+const BACKUPS_MAX: usize = 5;
+const BACKUPS_DIR: &str = "backups";
+
+impl Editor {
+    /// The file with the autosaved unsaved changes of the level.
+    fn recovery_path(&self) -> PathBuf {
+        let mut name = self.level_path.file_name().unwrap_or_default().to_owned();
+        name.push(".recovery");
+        self.level_path.with_file_name(name)
+    }
+
+    /// Writes the unsaved changes to the recovery file.
+    pub(super) fn autosave(&mut self) {
+        self.autosave_timer = Time::new(AUTOSAVE_INTERVAL);
+        if !self.is_dirty() {
+            return;
+        }
+        #[cfg(not(target = "wasm32"))]
+        {
+            let path = self.recovery_path();
+            if util::report_warn(
+                util::write_atomic(&path, &self.world.level),
+                "Failed to write the recovery file",
+            )
+            .is_ok()
+            {
+                log::debug!("Autosaved the level at {path:?}");
+            }
+        }
+    }
+
+    pub(super) fn remove_recovery(&self) {
+        #[cfg(not(target = "wasm32"))]
+        {
+            let path = self.recovery_path();
+            if path.exists() {
+                let _ = util::report_warn(
+                    std::fs::remove_file(path),
+                    "Failed to remove the recovery file",
+                );
+            }
+        }
+    }
+
+    /// Offers to recover the changes if the recovery file is newer than the level.
+    pub(super) fn check_recovery(&mut self) {
+        #[cfg(not(target = "wasm32"))]
+        {
+            let modified = |path: &Path| std::fs::metadata(path).and_then(|meta| meta.modified());
+            let recovery = self.recovery_path();
+            if let Ok(recovery_time) = modified(&recovery) {
+                let newer = modified(&self.level_path).map_or(true, |time| recovery_time > time);
+                if newer {
+                    self.dialog = Some(Dialog::Recover { path: recovery });
+                }
+            }
+        }
+    }
+
+    /// Replaces the level with the one from the recovery file.
+    /// The level stays unsaved until it is explicitly saved.
+    pub(super) fn recover(&mut self, path: &Path) -> anyhow::Result<()> {
+        #[cfg(not(target = "wasm32"))]
+        {
+            let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+            self.world.level = serde_json::from_reader(reader)?;
+            self.render_cache = RenderCache::calculate(&self.world, &self.geng);
+            self.selection.clear();
+            self.mark_dirty();
+            log::info!("Recovered the level from {path:?}");
+        }
+        Ok(())
+    }
+}
+
+/// Copies the current contents of the level file into a timestamped backup,
+/// removing the oldest backups beyond [BACKUPS_MAX].
+pub(super) fn backup(path: &Path) -> anyhow::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    let dir = path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(BACKUPS_DIR);
+    std::fs::create_dir_all(&dir)?;
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    std::fs::copy(path, dir.join(format!("{stem}-{timestamp}.json")))?;
+
+    let prefix = format!("{stem}-");
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let timestamp = path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(|time| time.parse::<u64>().ok());
+        if let Some(timestamp) = timestamp {
+            backups.push((timestamp, path));
+        }
+    }
+    backups.sort();
+    let excess = backups.len().saturating_sub(BACKUPS_MAX);
+    for (_, path) in backups.into_iter().take(excess) {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
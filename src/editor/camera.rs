@@ -0,0 +1,115 @@
+use super::*;
+
+const FOV_MIN: f32 = 5.0;
+const FOV_MAX: f32 = 500.0;
+/// Relative change of the fov per wheel step.
+const ZOOM_SPEED: f32 = 0.1;
+/// Extra space around the framed area relative to its size.
+const FRAME_MARGIN: f32 = 0.2;
+/// Height of the minimap relative to the screen.
+const MINIMAP_SIZE: f32 = 0.25;
+/// Distance from the minimap to the edge of the screen relative to the screen height.
+const MINIMAP_PADDING: f32 = 0.02;
+
+/// Placement of the minimap on the screen.
+pub(super) struct Minimap {
+    /// The part of the screen occupied by the minimap in pixels.
+    pub screen: Aabb2<f32>,
+    /// The part of the world shown on the minimap.
+    pub world: Aabb2<f32>,
+}
+
+impl Minimap {
+    pub fn world_to_screen(&self, position: vec2<f32>) -> vec2<f32> {
+        let t = (position - self.world.min) / self.world.size();
+        self.screen.min + self.screen.size() * t
+    }
+
+    pub fn screen_to_world(&self, position: vec2<f32>) -> vec2<f32> {
+        let t = (position - self.screen.min) / self.screen.size();
+        self.world.min + self.world.size() * t
+    }
+}
+
+impl Editor {
+    /// Zooms the camera keeping the world point under the cursor in place.
+    pub(super) fn zoom(&mut self, delta: f64, cursor: vec2<f64>) {
+        if delta == 0.0 {
+            return;
+        }
+        let before = self.screen_to_world(cursor);
+        let camera = &mut self.world.camera;
+        let scale = (1.0 - ZOOM_SPEED).powf(delta.signum() as f32);
+        camera.fov = (camera.fov * scale).clamp(FOV_MIN, FOV_MAX);
+        let after = self.screen_to_world(cursor);
+        self.world.camera.center += (before - after).map(Coord::as_f32);
+    }
+
+    /// Moves the camera so that the `from` world point is under the cursor.
+    pub(super) fn pan(&mut self, from: vec2<Coord>, cursor: vec2<f64>) {
+        let delta = from - self.screen_to_world(cursor);
+        self.world.camera.center += delta.map(Coord::as_f32);
+    }
+
+    /// Fits the area into the screen.
+    fn frame(&mut self, area: Aabb2<Coord>) {
+        let area = area.map(Coord::as_f32);
+        let framebuffer_size = self.framebuffer_size.map(|x| x as f32);
+        let aspect = framebuffer_size.x / framebuffer_size.y;
+        let size = area.size();
+        let fov = size.y.max(size.x / aspect) * (1.0 + FRAME_MARGIN);
+        self.world.camera.center = area.center();
+        self.world.camera.fov = fov.clamp(FOV_MIN, FOV_MAX);
+    }
+
+    /// Fits the selected entities into the screen, or the whole level if nothing is selected.
+    pub(super) fn frame_selection(&mut self) {
+        let area = self
+            .selection
+            .iter()
+            .filter_map(|&entity| self.entity_collider(entity))
+            .map(|collider| collider.bounding_box())
+            .reduce(aabb_union);
+        match area {
+            Some(area) => self.frame(area),
+            None => self.frame_level(),
+        }
+    }
+
+    pub(super) fn frame_level(&mut self) {
+        self.frame(self.world.level.bounds());
+    }
+
+    pub(super) fn minimap(&self) -> Option<Minimap> {
+        if !self.show_minimap {
+            return None;
+        }
+        let framebuffer_size = self.framebuffer_size.map(|x| x as f32);
+        let world = self
+            .world
+            .level
+            .bounds()
+            .map(Coord::as_f32)
+            .extend_uniform(1.0);
+        let height = framebuffer_size.y * MINIMAP_SIZE;
+        let size = world.size();
+        let width = (height * size.x / size.y).min(framebuffer_size.x * MINIMAP_SIZE * 2.0);
+        let height = width * size.y / size.x;
+        let padding = framebuffer_size.y * MINIMAP_PADDING;
+        let min = vec2(padding, padding);
+        Some(Minimap {
+            screen: Aabb2::point(min).extend_positive(vec2(width, height)),
+            world,
+        })
+    }
+
+    /// Returns the world position under the cursor if the cursor is over the minimap.
+    pub(super) fn minimap_target(&self, cursor: vec2<f64>) -> Option<vec2<f32>> {
+        let minimap = self.minimap()?;
+        let cursor = cursor.map(|x| x as f32);
+        minimap
+            .screen
+            .contains(cursor)
+            .then(|| minimap.screen_to_world(cursor))
+    }
+}
@@ -0,0 +1,217 @@
+use super::*;
+
+/// Offset of the duplicated entities from the originals.
+const DUPLICATE_OFFSET: vec2<f32> = vec2(1.0, -1.0);
+
+/// A part of the level that can be copied between the editor sessions.
+#[derive(Serialize, Deserialize)]
+pub(super) struct LevelFragment {
+    /// The center of the copied entities.
+    pub origin: vec2<Coord>,
+    #[serde(default)]
+    pub waypoints: Vec<Waypoint>,
+    #[serde(default)]
+    pub obstacles: Vec<Obstacle>,
+    #[serde(default)]
+    pub lamps: Vec<Lamp>,
+    #[serde(default)]
+    pub props: Vec<Prop>,
+    #[serde(default)]
+    pub hiding_zones: Vec<HidingZone>,
+    #[serde(default)]
+    pub pickups: Vec<Pickup>,
+}
+
+/// The file used to share the clipboard between the editor sessions.
+fn clipboard_path() -> std::path::PathBuf {
+    std::env::temp_dir().join("shadow-delivery-clipboard.json")
+}
+
+fn write_clipboard(fragment: &LevelFragment) -> anyhow::Result<()> {
+    let writer = std::io::BufWriter::new(std::fs::File::create(clipboard_path())?);
+    serde_json::to_writer(writer, fragment)?;
+    Ok(())
+}
+
+/// Returns `None` if nothing has been copied yet.
+fn read_clipboard() -> anyhow::Result<Option<LevelFragment>> {
+    let file = match std::fs::File::open(clipboard_path()) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let reader = std::io::BufReader::new(file);
+    Ok(Some(serde_json::from_reader(reader)?))
+}
+
+impl Editor {
+    pub(super) fn copy(&mut self) {
+        if self.selection.is_empty() {
+            return;
+        }
+        let fragment = self.selection_fragment();
+        #[cfg(not(target = "wasm32"))]
+        {
+            let _ = util::report_warn(
+                write_clipboard(&fragment),
+                "Failed to write the clipboard file",
+            );
+        }
+        self.clipboard = Some(fragment);
+    }
+
+    /// Pastes the clipboard so that its center is at the position.
+    pub(super) fn paste(&mut self, position: vec2<Coord>) {
+        #[cfg(not(target = "wasm32"))]
+        {
+            // The clipboard file might have been updated by another session
+            if let Ok(Some(fragment)) =
+                util::report_warn(read_clipboard(), "Failed to read the clipboard file")
+            {
+                self.clipboard = Some(fragment);
+            }
+        }
+
+        let Some(fragment) = self.clipboard.take() else {
+            return;
+        };
+        let offset = position - fragment.origin;
+        self.selection = self.insert_fragment(&fragment, offset);
+        self.clipboard = Some(fragment);
+    }
+
+    pub(super) fn duplicate(&mut self) {
+        let fragment = self.selection_fragment();
+        self.selection = self.insert_fragment(&fragment, DUPLICATE_OFFSET.map(Coord::new));
+    }
+
+    /// Collects the selected entities into a fragment.
+    fn selection_fragment(&self) -> LevelFragment {
+        let level = &self.world.level;
+        let mut fragment = LevelFragment {
+            origin: vec2::ZERO,
+            waypoints: Vec::new(),
+            obstacles: Vec::new(),
+            lamps: Vec::new(),
+            props: Vec::new(),
+            hiding_zones: Vec::new(),
+            pickups: Vec::new(),
+        };
+        let mut bounds: Option<Aabb2<Coord>> = None;
+        for &entity in &self.selection {
+            if let Some(collider) = self.entity_collider(entity) {
+                let aabb = collider.bounding_box();
+                bounds = Some(bounds.map_or(aabb, |bounds| aabb_union(bounds, aabb)));
+            }
+            match entity {
+                Entity::Spawn => {}
+                Entity::Waypoint(id) => fragment.waypoints.push(Waypoint {
+                    collider: *level.waypoints.collider.get(id).unwrap(),
+                }),
+                Entity::Obstacle(id) => fragment.obstacles.push(Obstacle {
+                    difficulty: *level.obstacles.difficulty.get(id).unwrap(),
+                    collider: *level.obstacles.collider.get(id).unwrap(),
+                    lights: level.obstacles.lights.get(id).unwrap().clone(),
+                    path: level.obstacles.path.get(id).unwrap().clone(),
+                    alert: None,
+                    chunk: None,
+                }),
+                Entity::Lamp(id) => fragment.lamps.push(Lamp {
+                    collider: *level.lamps.collider.get(id).unwrap(),
+                    light: *level.lamps.light.get(id).unwrap(),
+                    state: level.lamps.state.get(id).unwrap().clone(),
+                    up_time: *level.lamps.up_time.get(id).unwrap(),
+                    down_time: *level.lamps.down_time.get(id).unwrap(),
+                }),
+                Entity::Prop(id) => fragment.props.push(Prop {
+                    collider: *level.props.collider.get(id).unwrap(),
+                    prop: level.props.prop.get(id).unwrap().clone(),
+                }),
+                Entity::HidingZone(id) => fragment.hiding_zones.push(HidingZone {
+                    collider: *level.hiding_zones.collider.get(id).unwrap(),
+                }),
+                Entity::Pickup(id) => fragment.pickups.push(Pickup {
+                    collider: *level.pickups.collider.get(id).unwrap(),
+                    item: *level.pickups.item.get(id).unwrap(),
+                }),
+            }
+        }
+        fragment.origin = bounds.map_or(vec2::ZERO, |bounds| bounds.center());
+        fragment
+    }
+
+    /// Inserts the copies of the entities from the fragment moved by the offset.
+    /// Returns the inserted entities.
+    fn insert_fragment(&mut self, fragment: &LevelFragment, offset: vec2<Coord>) -> Vec<Entity> {
+        let level = &mut self.world.level;
+        let mut inserted = Vec::new();
+        for waypoint in &fragment.waypoints {
+            let mut collider = waypoint.collider;
+            collider.translate(offset);
+            let id = level.waypoints.insert(Waypoint { collider });
+            inserted.push(Entity::Waypoint(id));
+        }
+        for obstacle in &fragment.obstacles {
+            let mut collider = obstacle.collider;
+            collider.translate(offset);
+            let path = obstacle.path.clone().map(|mut path| {
+                for point in &mut path.points {
+                    *point += offset;
+                }
+                path
+            });
+            let id = level.obstacles.insert(Obstacle {
+                difficulty: obstacle.difficulty,
+                collider,
+                lights: obstacle.lights.clone(),
+                path,
+                alert: None,
+                chunk: None,
+            });
+            inserted.push(Entity::Obstacle(id));
+        }
+        for lamp in &fragment.lamps {
+            let mut collider = lamp.collider;
+            collider.translate(offset);
+            let id = level.lamps.insert(Lamp {
+                collider,
+                light: lamp.light,
+                state: lamp.state.clone(),
+                up_time: lamp.up_time,
+                down_time: lamp.down_time,
+            });
+            inserted.push(Entity::Lamp(id));
+        }
+        for prop in &fragment.props {
+            let mut collider = prop.collider;
+            collider.translate(offset);
+            let id = level.props.insert(Prop {
+                collider,
+                prop: prop.prop.clone(),
+            });
+            inserted.push(Entity::Prop(id));
+        }
+        for zone in &fragment.hiding_zones {
+            let mut collider = zone.collider;
+            collider.translate(offset);
+            let id = level.hiding_zones.insert(HidingZone { collider });
+            inserted.push(Entity::HidingZone(id));
+        }
+        for pickup in &fragment.pickups {
+            let mut collider = pickup.collider;
+            collider.translate(offset);
+            let id = level.pickups.insert(Pickup {
+                collider,
+                item: pickup.item,
+            });
+            inserted.push(Entity::Pickup(id));
+        }
+        if !fragment.props.is_empty() {
+            self.render_cache.invalidate_props();
+        }
+        if !inserted.is_empty() {
+            self.mark_dirty();
+        }
+        inserted
+    }
+}
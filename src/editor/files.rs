@@ -0,0 +1,229 @@
+use super::*;
+
+pub(super) enum Dialog {
+    /// Lists the levels in the directory of the current level.
+    Open { files: Vec<(String, PathBuf)> },
+    /// Asks for the name of the new level file.
+    SaveAs { name: String },
+    /// Warns that saving as the name replaces another level.
+    ConfirmOverwrite { name: String },
+    /// Warns about the unsaved changes before the action.
+    ConfirmDiscard { action: FileAction },
+    /// Offers to recover the autosaved changes.
+    Recover { path: PathBuf },
+    /// Sets up the parameters of the generated level.
+    Generate { config: GeneratorConfig },
+}
+
+/// Action chosen in the dialog.
+pub(super) enum DialogAction {
+    Open(PathBuf),
+    Save,
+    Discard,
+    Recover,
+    Generate,
+    Cancel,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum FileAction {
+    Open(PathBuf),
+    /// Replaces the level with a generated one.
+    Generate {
+        config: GeneratorConfig,
+    },
+    /// Returns from the editor with Escape.
+    /// Closing the window quits without the confirmation,
+    /// the unsaved changes are left only in the recovery file.
+    Quit,
+}
+
+impl Editor {
+    /// Whether the level has changed since it was last saved or loaded.
+    pub(super) fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Called by every operation that edits the level.
+    pub(super) fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    pub(super) fn mark_saved(&mut self) {
+        self.dirty = false;
+    }
+
+    /// Directory with the levels that can be opened.
+    fn levels_dir(&self) -> PathBuf {
+        self.level_path
+            .parent()
+            .map(|dir| dir.to_path_buf())
+            .unwrap_or_else(run_dir)
+    }
+
+    pub(super) fn open_dialog(&mut self) {
+        let files = util::report_err(list_levels(&self.levels_dir()))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                (name.into_owned(), path)
+            })
+            .collect();
+        self.dialog = Some(Dialog::Open { files });
+    }
+
+    pub(super) fn save_as_dialog(&mut self) {
+        let name = self
+            .level_path
+            .file_stem()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_owned();
+        self.dialog = Some(Dialog::SaveAs { name });
+    }
+
+    /// Opens the generator parameters from the last generated level with a new seed.
+    pub(super) fn generate_dialog(&mut self) {
+        let config = GeneratorConfig {
+            seed: thread_rng().gen(),
+            ..self.generator.clone()
+        };
+        self.dialog = Some(Dialog::Generate { config });
+    }
+
+    /// Performs the action, asking for confirmation if there are unsaved changes.
+    pub(super) fn request(&mut self, action: FileAction) {
+        if self.is_dirty() {
+            self.dialog = Some(Dialog::ConfirmDiscard { action });
+        } else {
+            self.perform(action);
+        }
+    }
+
+    /// Performs the action discarding the unsaved changes.
+    pub(super) fn perform(&mut self, action: FileAction) {
+        self.dialog = None;
+        match action {
+            FileAction::Open(path) => {
+                let old_path = std::mem::replace(&mut self.level_path, path);
+                if util::report_err(self.load()).is_err() {
+                    self.level_path = old_path;
+                }
+            }
+            FileAction::Generate { config } => {
+                self.world.level = generate_level(&config);
+                self.render_cache = RenderCache::calculate(&self.world, &self.geng);
+                self.selection.clear();
+                self.mark_dirty();
+                self.frame_level();
+                log::info!("Generated a level with seed {}", config.seed);
+            }
+            FileAction::Quit => {
+                self.transition = Some(geng::state::Transition::Pop);
+            }
+        }
+    }
+
+    /// Level file with the name in the directory of the current level.
+    fn level_file(&self, name: &str) -> PathBuf {
+        self.levels_dir().join(format!("{name}.json"))
+    }
+
+    /// Saves the level under the name, asking for confirmation if it replaces another level.
+    pub(super) fn request_save_as(&mut self, name: String) {
+        let path = self.level_file(&name);
+        if path != self.level_path && path.exists() {
+            self.dialog = Some(Dialog::ConfirmOverwrite { name });
+        } else {
+            self.save_as(&name);
+        }
+    }
+
+    pub(super) fn save_as(&mut self, name: &str) {
+        let path = self.level_file(name);
+        let old_path = std::mem::replace(&mut self.level_path, path);
+        if util::report_err(self.save()).is_err() {
+            self.level_path = old_path;
+        }
+    }
+
+    pub(super) fn dialog_action(&mut self, action: DialogAction) {
+        let Some(dialog) = self.dialog.take() else {
+            return;
+        };
+        match (action, dialog) {
+            (DialogAction::Open(path), _) => self.request(FileAction::Open(path)),
+            (DialogAction::Save, Dialog::SaveAs { name }) if !name.is_empty() => {
+                self.request_save_as(name)
+            }
+            (DialogAction::Save, Dialog::ConfirmOverwrite { name }) => self.save_as(&name),
+            (DialogAction::Discard, Dialog::ConfirmDiscard { action }) => {
+                // The changes are discarded deliberately
+                self.remove_recovery();
+                self.perform(action);
+            }
+            (DialogAction::Recover, Dialog::Recover { path }) => {
+                let _ = util::report_err(self.recover(&path));
+            }
+            (DialogAction::Discard, Dialog::Recover { .. }) => self.remove_recovery(),
+            (DialogAction::Generate, Dialog::Generate { config }) => {
+                self.generator = config.clone();
+                self.request(FileAction::Generate { config });
+            }
+            (DialogAction::Cancel, _) => {}
+            (_, dialog) => self.dialog = Some(dialog),
+        }
+    }
+
+    /// Handles the key press while a dialog is open.
+    pub(super) fn dialog_key(&mut self, key: geng::Key) {
+        match key {
+            geng::Key::Escape => self.dialog_action(DialogAction::Cancel),
+            geng::Key::Enter => match self.dialog {
+                Some(Dialog::SaveAs { .. } | Dialog::ConfirmOverwrite { .. }) => {
+                    self.dialog_action(DialogAction::Save)
+                }
+                Some(Dialog::ConfirmDiscard { .. }) => self.dialog_action(DialogAction::Discard),
+                Some(Dialog::Recover { .. }) => self.dialog_action(DialogAction::Recover),
+                Some(Dialog::Generate { .. }) => self.dialog_action(DialogAction::Generate),
+                _ => {}
+            },
+            _ => {
+                if let Some(Dialog::SaveAs { name }) = &mut self.dialog {
+                    if let geng::Key::Backspace = key {
+                        name.pop();
+                    } else {
+                        name.extend(key_char(key));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn list_levels(dir: &std::path::Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    #[cfg(not(target = "wasm32"))]
+    {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |ext| ext == "json") {
+                files.push(path);
+            }
+        }
+        files.sort();
+    }
+    Ok(files)
+}
+
+/// Converts the key into a character allowed in file names.
+fn key_char(key: geng::Key) -> Option<char> {
+    let name = format!("{key:?}");
+    let name = name.strip_prefix("Num").unwrap_or(&name);
+    match key {
+        geng::Key::Space => Some('_'),
+        _ if name.len() == 1 => name.chars().next().map(|c| c.to_ascii_lowercase()),
+        _ => None,
+    }
+}
@@ -0,0 +1,202 @@
+use super::*;
+
+/// Radius of the light handles that can be grabbed with the cursor.
+pub(super) const HANDLE_RADIUS: f32 = 0.2;
+/// Fraction of the light's distance at which the range handles are placed.
+const RANGE_HANDLE_DISTANCE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum LightHandle {
+    /// Moves the light relative to the obstacle.
+    Position,
+    /// Controls the `angle` and the `max_distance`.
+    Direction,
+    /// Controls the `angle_range`.
+    Range,
+}
+
+/// Light handles in world coordinates.
+pub(super) struct LightGizmo {
+    pub position: vec2<f32>,
+    pub direction: vec2<f32>,
+    pub range: [vec2<f32>; 2],
+    /// The light transformed into world coordinates.
+    pub light: Spotlight,
+}
+
+impl Editor {
+    pub(super) fn light_click(&mut self, world_pos: vec2<Coord>, button: MouseButton) {
+        let Some(obstacle) = self.selected_obstacle() else {
+            if let MouseButton::Left = button {
+                self.select_obstacle(world_pos);
+            }
+            return;
+        };
+
+        let handle = self.find_light_handle(obstacle, world_pos);
+        match button {
+            MouseButton::Left => {
+                if let Some((light, handle)) = handle {
+                    self.drag = Some(Drag {
+                        from: world_pos,
+                        target: DragTarget::Light {
+                            obstacle,
+                            light,
+                            handle,
+                        },
+                    });
+                    return;
+                }
+
+                if let Some(Entity::Obstacle(id)) = self.find_target(world_pos) {
+                    if id != obstacle {
+                        self.selection = vec![Entity::Obstacle(id)];
+                        return;
+                    }
+                }
+
+                let collider = *self.world.level.obstacles.collider.get(obstacle).unwrap();
+                let position = to_local(&collider, world_pos);
+                let angle = if position == vec2::ZERO {
+                    0.0
+                } else {
+                    position.arg().as_f32()
+                };
+                let lights = self.world.level.obstacles.lights.get_mut(obstacle).unwrap();
+                lights.push(Spotlight {
+                    position,
+                    angle,
+                    ..default()
+                });
+                let light = lights.len() - 1;
+                self.mark_dirty();
+                self.drag = Some(Drag {
+                    from: world_pos,
+                    target: DragTarget::Light {
+                        obstacle,
+                        light,
+                        handle: LightHandle::Direction,
+                    },
+                });
+            }
+            MouseButton::Right => match handle {
+                Some((light, LightHandle::Position)) => {
+                    let lights = self.world.level.obstacles.lights.get_mut(obstacle).unwrap();
+                    lights.remove(light);
+                    self.mark_dirty();
+                }
+                Some(_) => {}
+                None => {
+                    self.selection.clear();
+                }
+            },
+            _ => {}
+        }
+    }
+
+    pub(super) fn drag_light(
+        &mut self,
+        obstacle: usize,
+        light: usize,
+        handle: LightHandle,
+        world_pos: vec2<Coord>,
+    ) {
+        let Some(&collider) = self.world.level.obstacles.collider.get(obstacle) else {
+            return;
+        };
+        self.mark_dirty();
+        let Some(light) = self
+            .world
+            .level
+            .obstacles
+            .lights
+            .get_mut(obstacle)
+            .and_then(|lights| lights.get_mut(light))
+        else {
+            return;
+        };
+
+        let local = to_local(&collider, world_pos);
+        let delta = local - light.position;
+        match handle {
+            LightHandle::Position => {
+                light.position = local;
+            }
+            LightHandle::Direction => {
+                if delta != vec2::ZERO {
+                    light.angle = delta.arg().as_f32();
+                }
+                light.max_distance = delta.len().max(Coord::new(HANDLE_RADIUS));
+            }
+            LightHandle::Range => {
+                if delta != vec2::ZERO {
+                    let angle = normalize_radians(delta.arg().as_f32() - light.angle);
+                    light.angle_range = (angle.abs() * 2.0).clamp(0.0, f32::PI * 2.0);
+                }
+            }
+        }
+    }
+
+    /// Finds the handle of a light of the obstacle under the cursor.
+    fn find_light_handle(
+        &self,
+        obstacle: usize,
+        position: vec2<Coord>,
+    ) -> Option<(usize, LightHandle)> {
+        let position = position.map(Coord::as_f32);
+        let hovered = |handle: vec2<f32>| (handle - position).len() < HANDLE_RADIUS;
+        self.light_gizmos(obstacle)
+            .into_iter()
+            .enumerate()
+            .find_map(|(light, gizmo)| {
+                if hovered(gizmo.position) {
+                    Some((light, LightHandle::Position))
+                } else if hovered(gizmo.direction) {
+                    Some((light, LightHandle::Direction))
+                } else if gizmo.range.into_iter().any(hovered) {
+                    Some((light, LightHandle::Range))
+                } else {
+                    None
+                }
+            })
+    }
+
+    pub(super) fn light_gizmos(&self, obstacle: usize) -> Vec<LightGizmo> {
+        let Some(collider) = self.world.level.obstacles.collider.get(obstacle) else {
+            return Vec::new();
+        };
+        let Some(lights) = self.world.level.obstacles.lights.get(obstacle) else {
+            return Vec::new();
+        };
+        let rotation = collider.rotation.as_radians();
+        let offset = collider.pos().map(Coord::as_f32);
+        lights
+            .iter()
+            .map(|(_, light)| {
+                let position = light.position.map(Coord::as_f32).rotate(rotation) + offset;
+                let angle = light.angle + rotation;
+                let distance = light.max_distance.as_f32();
+                let point = |angle: f32, distance: f32| {
+                    position + Angle::new_radians(angle).unit_direction() * distance
+                };
+                let half_range = light.angle_range / 2.0;
+                LightGizmo {
+                    position,
+                    direction: point(angle, distance),
+                    range: [-half_range, half_range]
+                        .map(|delta| point(angle + delta, distance * RANGE_HANDLE_DISTANCE)),
+                    light: Spotlight {
+                        position: position.map(Coord::new),
+                        angle,
+                        ..*light
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+/// Converts the world position into the coordinates relative to the collider.
+fn to_local(collider: &Collider, position: vec2<Coord>) -> vec2<Coord> {
+    (position - collider.pos()).rotate(Coord::new(-collider.rotation.as_radians()))
+}
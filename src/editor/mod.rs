@@ -1,17 +1,31 @@
 use std::path::PathBuf;
 
-use crate::{model::*, render::RenderCache};
+use crate::{game::Game, model::*, render::RenderCache};
 
 use super::*;
 
+mod backup;
+mod camera;
+mod clipboard;
+mod files;
+mod lights;
+mod path;
 mod render;
-
+mod roads;
+mod transform;
+mod ui;
+
+use backup::*;
+use camera::*;
+use clipboard::*;
+use files::*;
 use geng::MouseButton;
+use lights::*;
 use render::*;
+use transform::*;
 
 pub struct Editor {
     geng: Geng,
-    #[allow(dead_code)]
     assets: Rc<Assets>,
     render: EditorRender,
     render_cache: RenderCache,
@@ -22,7 +36,27 @@ pub struct Editor {
     mode: EditorMode,
     drag: Option<Drag>,
     cursor_pos: vec2<Coord>,
-    props: Vec<PropType>,
+    /// Last cursor position on the screen.
+    cursor_screen: vec2<f64>,
+    /// Selected entities that are moved, copied and removed together.
+    /// A single selected entity can be transformed,
+    /// and a single selected obstacle's path or lights are edited in the respective modes.
+    selection: Vec<Entity>,
+    snapping: Snapping,
+    clipboard: Option<LevelFragment>,
+    transition: Option<geng::state::Transition>,
+    /// Whether the obstacles are simulated along their paths.
+    preview: bool,
+    show_minimap: bool,
+    dialog: Option<Dialog>,
+    /// Action chosen in the dialog ui, performed on the next update.
+    ui_action: Option<DialogAction>,
+    /// Whether the level has unsaved changes.
+    dirty: bool,
+    /// Time until the next autosave to the recovery file.
+    autosave_timer: Time,
+    /// Parameters of the last generated level.
+    generator: GeneratorConfig,
 }
 
 struct Drag {
@@ -30,14 +64,46 @@ struct Drag {
     target: DragTarget,
 }
 
-enum DragTarget {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Entity {
     Spawn,
     Waypoint(usize),
     Obstacle(usize),
     Lamp(usize),
     Prop(usize),
+    HidingZone(usize),
+    Pickup(usize),
+}
+
+enum DragTarget {
+    /// Moves the camera so that the point under the cursor stays in place.
+    Pan,
+    /// Moves the camera to the point on the minimap.
+    Minimap,
+    /// Moves all selected entities.
+    Selection,
+    BoxSelect,
+    Resize {
+        entity: Entity,
+        /// Index of the dragged corner in [Collider::vertices].
+        corner: usize,
+    },
+    Rotate(Entity),
     NewObstacle,
     NewProp(usize),
+    NewHidingZone,
+    PathPoint {
+        obstacle: usize,
+        point: usize,
+    },
+    Light {
+        obstacle: usize,
+        light: usize,
+        handle: LightHandle,
+    },
+    Road {
+        erase: bool,
+    },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -47,16 +113,22 @@ enum EditorMode {
     Obstacle,
     Lamp,
     Prop(usize),
+    Path,
+    Light,
+    /// Paints the road cells on a grid.
+    Road,
+    HidingZone,
+    Pickup(ItemKind),
 }
 
 impl Editor {
     pub fn new(geng: &Geng, assets: &Rc<Assets>, level: Level, level_path: PathBuf) -> Self {
         let world = World::new(assets, level);
-        Self {
+        let mut editor = Self {
             geng: geng.clone(),
             assets: assets.clone(),
             render: EditorRender::new(geng, assets),
-            render_cache: RenderCache::calculate(&world, geng, assets),
+            render_cache: RenderCache::calculate(&world, geng),
             framebuffer_size: vec2(1, 1),
             draw_hitboxes: true,
             world,
@@ -64,13 +136,22 @@ impl Editor {
             mode: EditorMode::Spawn,
             drag: None,
             cursor_pos: vec2::ZERO,
-            props: [
-                "road", "road_v", "road_ur", "road_ul", "road_rd", "road_dl", "bricks",
-            ]
-            .into_iter()
-            .map(String::from)
-            .collect(),
-        }
+            cursor_screen: vec2::ZERO,
+            selection: Vec::new(),
+            snapping: default(),
+            clipboard: None,
+            transition: None,
+            preview: false,
+            show_minimap: true,
+            dialog: None,
+            ui_action: None,
+            dirty: false,
+            autosave_timer: Time::new(AUTOSAVE_INTERVAL),
+            generator: default(),
+        };
+        editor.mark_saved();
+        editor.check_recovery();
+        editor
     }
 
     pub fn load(&mut self) -> anyhow::Result<()> {
@@ -78,16 +159,22 @@ impl Editor {
         {
             let reader = std::io::BufReader::new(std::fs::File::open(&self.level_path)?);
             self.world.level = serde_json::from_reader(reader)?;
+            self.render_cache = RenderCache::calculate(&self.world, &self.geng);
+            self.selection.clear();
+            self.mark_saved();
             log::info!("Loaded level from {:?}", self.level_path);
+            self.check_recovery();
         }
         Ok(())
     }
 
-    pub fn save(&self) -> anyhow::Result<()> {
+    pub fn save(&mut self) -> anyhow::Result<()> {
         #[cfg(not(target = "wasm32"))]
         {
-            let writer = std::io::BufWriter::new(std::fs::File::create(&self.level_path)?);
-            serde_json::to_writer_pretty(writer, &self.world.level)?;
+            util::report_warn(backup(&self.level_path), "Failed to back up the level").ok();
+            util::write_atomic(&self.level_path, &self.world.level)?;
+            self.mark_saved();
+            self.remove_recovery();
             log::info!("Saved the level at {:?}", self.level_path);
         }
         Ok(())
@@ -103,41 +190,154 @@ impl Editor {
             .map(Coord::new)
     }
 
-    fn remove(&mut self, target: DragTarget) {
-        match target {
-            DragTarget::Waypoint(id) => {
-                self.world.level.waypoints.remove(id);
-            }
-            DragTarget::Obstacle(id) => {
-                self.world.level.obstacles.remove(id);
-            }
-            DragTarget::Prop(id) => {
-                self.world.level.props.remove(id);
+    fn selected_obstacle(&self) -> Option<usize> {
+        match self.selection.as_slice() {
+            &[Entity::Obstacle(id)] => Some(id),
+            _ => None,
+        }
+    }
+
+    fn remove(&mut self, mut entities: Vec<Entity>) {
+        if entities.is_empty() {
+            return;
+        }
+        self.mark_dirty();
+        // Remove from the back so that the ids of the other entities stay valid
+        entities.sort_by_key(|entity| match *entity {
+            Entity::Spawn => 0,
+            Entity::Waypoint(id)
+            | Entity::Obstacle(id)
+            | Entity::Lamp(id)
+            | Entity::Prop(id)
+            | Entity::HidingZone(id)
+            | Entity::Pickup(id) => id,
+        });
+        for entity in entities.into_iter().rev() {
+            match entity {
+                Entity::Spawn => {}
+                Entity::Waypoint(id) => {
+                    self.world.level.waypoints.remove(id);
+                }
+                Entity::Obstacle(id) => {
+                    self.world.level.obstacles.remove(id);
+                    self.render_cache.invalidate_removed(id);
+                }
+                Entity::Lamp(id) => {
+                    self.world.level.lamps.remove(id);
+                }
+                Entity::Prop(id) => {
+                    self.world.level.props.remove(id);
+                    self.render_cache.invalidate_props();
+                }
+                Entity::HidingZone(id) => {
+                    self.world.level.hiding_zones.remove(id);
+                }
+                Entity::Pickup(id) => {
+                    self.world.level.pickups.remove(id);
+                }
             }
-            _ => {}
         }
+        // Ids might have shifted
+        self.selection.clear();
+    }
+
+    /// Starts the game with the current level.
+    /// If `spawn_point` is specified, the player spawns there instead.
+    fn playtest(&mut self, spawn_point: Option<vec2<Coord>>) {
+        let mut level = self.world.level.clone();
+        if let Some(spawn_point) = spawn_point {
+            level.spawn_point = spawn_point;
+        }
+        let game = Game::playtest(&self.geng, &self.assets, level);
+        self.transition = Some(geng::state::Transition::Push(Box::new(game)));
     }
 
     fn click(&mut self, position: vec2<f64>, button: MouseButton) {
         let world_pos = self.screen_to_world(position);
 
-        if let Some(target) = self.find_target(world_pos) {
-            match button {
-                MouseButton::Left if self.geng.window().is_key_pressed(geng::Key::LCtrl) => {
-                    self.drag = Some(Drag {
-                        from: world_pos,
-                        target,
-                    });
+        if let MouseButton::Middle = button {
+            self.drag = Some(Drag {
+                from: world_pos,
+                target: DragTarget::Pan,
+            });
+            return;
+        }
+
+        if let Some(target) = self.minimap_target(position) {
+            if let MouseButton::Left = button {
+                self.world.camera.center = target;
+                self.drag = Some(Drag {
+                    from: world_pos,
+                    target: DragTarget::Minimap,
+                });
+            }
+            return;
+        }
+
+        if let MouseButton::Left = button {
+            if let Some(target) = self.find_transform_handle(world_pos) {
+                self.drag = Some(Drag {
+                    from: world_pos,
+                    target,
+                });
+                return;
+            }
+        }
+
+        if !self.geng.window().is_key_pressed(geng::Key::LCtrl) {
+            match self.mode {
+                EditorMode::Path => {
+                    self.path_click(world_pos, button);
                     return;
                 }
-                MouseButton::Right => {
-                    self.remove(target);
+                EditorMode::Light => {
+                    self.light_click(world_pos, button);
+                    return;
+                }
+                EditorMode::Road => {
+                    self.road_click(world_pos, button);
                     return;
                 }
                 _ => {}
             }
         }
 
+        let ctrl = self.geng.window().is_key_pressed(geng::Key::LCtrl);
+        let shift = self.geng.window().is_key_pressed(geng::Key::LShift);
+        match (self.find_target(world_pos), button) {
+            (Some(target), MouseButton::Left) if ctrl => {
+                if shift {
+                    // Toggle the entity in the selection
+                    if let Some(i) = self.selection.as_slice().iter().position(|&e| e == target) {
+                        self.selection.remove(i);
+                    } else {
+                        self.selection.push(target);
+                    }
+                    return;
+                }
+                if !self.selection.contains(&target) {
+                    self.selection = vec![target];
+                }
+                self.drag = Some(Drag {
+                    from: world_pos,
+                    target: DragTarget::Selection,
+                });
+                return;
+            }
+            (None, MouseButton::Left) if ctrl => {
+                self.drag = Some(Drag {
+                    from: world_pos,
+                    target: DragTarget::BoxSelect,
+                });
+                return;
+            }
+            (Some(target), MouseButton::Right) => {
+                self.remove(vec![target]);
+                return;
+            }
+            _ => {}
+        }
+
         if !matches!(button, MouseButton::Left) {
             return;
         }
@@ -149,10 +349,11 @@ impl Editor {
                 self.world.level.waypoints.insert(Waypoint {
                     collider: Collider::new(aabb),
                 });
+                self.mark_dirty();
             }
             EditorMode::Obstacle => {
                 self.drag = Some(Drag {
-                    from: world_pos,
+                    from: self.snap_point(world_pos, &[]),
                     target: DragTarget::NewObstacle,
                 });
             }
@@ -168,60 +369,84 @@ impl Editor {
                     up_time: Time::ONE,
                     down_time: Time::ZERO,
                 });
+                self.mark_dirty();
             }
             EditorMode::Prop(prop) => {
                 self.drag = Some(Drag {
-                    from: world_pos,
+                    from: self.snap_point(world_pos, &[]),
                     target: DragTarget::NewProp(prop),
                 });
             }
+            EditorMode::HidingZone => {
+                self.drag = Some(Drag {
+                    from: self.snap_point(world_pos, &[]),
+                    target: DragTarget::NewHidingZone,
+                });
+            }
+            EditorMode::Pickup(item) => {
+                let aabb = Aabb2::point(world_pos).extend_uniform(Coord::new(0.25));
+                self.world.level.pickups.insert(Pickup {
+                    collider: Collider::new(aabb),
+                    item,
+                });
+                self.mark_dirty();
+            }
+            EditorMode::Path | EditorMode::Light | EditorMode::Road => {}
         }
     }
 
     fn update_cursor(&mut self, position: vec2<f64>) {
         let world_pos = self.screen_to_world(position);
         self.cursor_pos = world_pos;
+        self.cursor_screen = position;
 
         if let Some(drag) = &mut self.drag {
             match drag.target {
-                DragTarget::Spawn => {
-                    self.world.level.spawn_point = world_pos;
+                DragTarget::Pan => {
+                    let from = drag.from;
+                    self.pan(from, position);
                 }
-                DragTarget::Waypoint(id) => {
-                    self.world
-                        .level
-                        .waypoints
-                        .collider
-                        .get_mut(id)
-                        .unwrap()
-                        .teleport(world_pos);
-                }
-                DragTarget::Obstacle(id) => {
-                    self.world
+                DragTarget::Minimap => {
+                    if let Some(target) = self.minimap_target(position) {
+                        self.world.camera.center = target;
+                    }
+                }
+                DragTarget::Road { erase } => {
+                    self.paint_road(world_pos, erase);
+                }
+                DragTarget::Selection => {
+                    let from = drag.from;
+                    let delta = self.move_selection(world_pos - from);
+                    if let Some(drag) = &mut self.drag {
+                        drag.from += delta;
+                    }
+                }
+                DragTarget::Resize { entity, corner } => {
+                    self.resize_entity(entity, corner, world_pos);
+                }
+                DragTarget::Rotate(entity) => {
+                    self.rotate_entity(entity, world_pos);
+                }
+                DragTarget::PathPoint { obstacle, point } => {
+                    let pos = self
+                        .world
                         .level
                         .obstacles
-                        .collider
-                        .get_mut(id)
-                        .unwrap()
-                        .teleport(world_pos);
+                        .path
+                        .get_mut(obstacle)
+                        .and_then(|path| path.as_mut())
+                        .and_then(|path| path.points.get_mut(point));
+                    if let Some(pos) = pos {
+                        *pos = world_pos;
+                        self.mark_dirty();
+                    }
                 }
-                DragTarget::Lamp(id) => {
-                    self.world
-                        .level
-                        .lamps
-                        .collider
-                        .get_mut(id)
-                        .unwrap()
-                        .teleport(world_pos);
-                }
-                DragTarget::Prop(id) => {
-                    self.world
-                        .level
-                        .props
-                        .collider
-                        .get_mut(id)
-                        .unwrap()
-                        .teleport(world_pos);
+                DragTarget::Light {
+                    obstacle,
+                    light,
+                    handle,
+                } => {
+                    self.drag_light(obstacle, light, handle, world_pos);
                 }
                 _ => {}
             }
@@ -232,61 +457,117 @@ impl Editor {
         if let Some(drag) = self.drag.take() {
             match drag.target {
                 DragTarget::NewObstacle => {
-                    let aabb = Aabb2::from_corners(drag.from, self.cursor_pos);
+                    let aabb =
+                        Aabb2::from_corners(drag.from, self.snap_point(self.cursor_pos, &[]));
                     self.world.level.obstacles.insert(Obstacle {
                         collider: Collider::new(aabb),
                         ..default()
                     });
+                    self.mark_dirty();
                 }
                 DragTarget::NewProp(prop) => {
-                    let prop = self.props.get(prop).unwrap().clone();
-                    let aabb = Aabb2::from_corners(drag.from, self.cursor_pos);
+                    let config = &self.assets.sprites.props.configs()[prop];
+                    let to = self.snap_point(self.cursor_pos, &[]);
+                    let aabb = if to == drag.from {
+                        // Place the prop of the default size on click
+                        Aabb2::point(drag.from).extend_symmetric(config.size / Coord::new(2.0))
+                    } else {
+                        Aabb2::from_corners(drag.from, to)
+                    };
                     self.world.level.props.insert(Prop {
                         collider: Collider::new(aabb),
-                        prop,
+                        prop: config.name.clone(),
+                    });
+                    self.render_cache.invalidate_props();
+                    self.mark_dirty();
+                }
+                DragTarget::NewHidingZone => {
+                    let aabb =
+                        Aabb2::from_corners(drag.from, self.snap_point(self.cursor_pos, &[]));
+                    self.world.level.hiding_zones.insert(HidingZone {
+                        collider: Collider::new(aabb),
                     });
+                    self.mark_dirty();
+                }
+                DragTarget::BoxSelect => {
+                    let area = Collider::new(Aabb2::from_corners(drag.from, self.cursor_pos));
+                    let selected = self
+                        .entities()
+                        .into_iter()
+                        .filter(|(_, collider)| collider.check(&area))
+                        .map(|(entity, _)| entity);
+                    if !self.geng.window().is_key_pressed(geng::Key::LShift) {
+                        self.selection.clear();
+                    }
+                    for entity in selected {
+                        if !self.selection.contains(&entity) {
+                            self.selection.push(entity);
+                        }
+                    }
                 }
                 _ => (),
             }
         }
     }
 
-    fn find_target(&self, position: vec2<Coord>) -> Option<DragTarget> {
+    /// Returns all entities in the level with their colliders.
+    fn entities(&self) -> Vec<(Entity, Collider)> {
         let mut player_collider = self.world.player.collider;
         player_collider.teleport(self.world.level.spawn_point);
 
-        #[derive(StructQuery)]
-        struct ColliderRef<'a> {
-            collider: &'a Collider,
-        }
-
-        let waypoints = query_collider_ref!(self.world.level.waypoints);
-        let obstacles = query_collider_ref!(self.world.level.obstacles);
-        let lamps = query_collider_ref!(self.world.level.lamps);
-        let props = query_collider_ref!(self.world.level.props);
-        let mut colliders = std::iter::once((
-            DragTarget::Spawn,
-            ColliderRef {
-                collider: &player_collider,
-            },
-        ))
-        .chain(
-            waypoints
-                .iter()
-                .map(|(id, item)| (DragTarget::Waypoint(id), item)),
-        )
-        .chain(
-            obstacles
-                .iter()
-                .map(|(id, item)| (DragTarget::Obstacle(id), item)),
-        )
-        .chain(lamps.iter().map(|(id, item)| (DragTarget::Lamp(id), item)))
-        .chain(props.iter().map(|(id, item)| (DragTarget::Prop(id), item)));
+        let level = &self.world.level;
+        std::iter::once((Entity::Spawn, player_collider))
+            .chain(
+                level
+                    .waypoints
+                    .collider
+                    .iter()
+                    .map(|(id, &collider)| (Entity::Waypoint(id), collider)),
+            )
+            .chain(
+                level
+                    .obstacles
+                    .collider
+                    .iter()
+                    .map(|(id, &collider)| (Entity::Obstacle(id), collider)),
+            )
+            .chain(
+                level
+                    .lamps
+                    .collider
+                    .iter()
+                    .map(|(id, &collider)| (Entity::Lamp(id), collider)),
+            )
+            .chain(
+                level
+                    .props
+                    .collider
+                    .iter()
+                    .map(|(id, &collider)| (Entity::Prop(id), collider)),
+            )
+            .chain(
+                level
+                    .hiding_zones
+                    .collider
+                    .iter()
+                    .map(|(id, &collider)| (Entity::HidingZone(id), collider)),
+            )
+            .chain(
+                level
+                    .pickups
+                    .collider
+                    .iter()
+                    .map(|(id, &collider)| (Entity::Pickup(id), collider)),
+            )
+            .collect()
+    }
 
+    fn find_target(&self, position: vec2<Coord>) -> Option<Entity> {
         let target = Collider::new(Aabb2::point(position).extend_uniform(Coord::new(0.01)));
-        colliders
-            .find(|(_, item)| item.collider.check(&target))
-            .map(|(id, _)| id)
+        self.entities()
+            .into_iter()
+            .find(|(_, collider)| collider.check(&target))
+            .map(|(entity, _)| entity)
     }
 }
 
@@ -302,13 +583,29 @@ impl geng::State for Editor {
             &geng::PixelPerfectCamera,
             &draw2d::Text::unit(
                 &**self.geng.default_font(),
-                format!("Mode: {:?}", self.mode),
+                format!(
+                    "Level: {}{}",
+                    self.level_path.display(),
+                    if self.is_dirty() { "*" } else { "" }
+                ),
                 Rgba::WHITE,
             )
             .scale_uniform(20.0)
             .align_bounding_box(vec2(0.0, 1.0))
             .translate(vec2(0.05, 0.95) * framebuffer_size),
         );
+        self.geng.draw2d().draw2d(
+            framebuffer,
+            &geng::PixelPerfectCamera,
+            &draw2d::Text::unit(
+                &**self.geng.default_font(),
+                format!("Mode: {:?}", self.mode),
+                Rgba::WHITE,
+            )
+            .scale_uniform(20.0)
+            .align_bounding_box(vec2(0.0, 1.0))
+            .translate(vec2(0.05, 0.9) * framebuffer_size),
+        );
         self.geng.draw2d().draw2d(
             framebuffer,
             &geng::PixelPerfectCamera,
@@ -324,6 +621,24 @@ impl geng::State for Editor {
             .align_bounding_box(vec2(1.0, 1.0))
             .translate(vec2(0.95, 0.95) * framebuffer_size),
         );
+        let toggle = |enabled: bool| if enabled { "on" } else { "off" };
+        self.geng.draw2d().draw2d(
+            framebuffer,
+            &geng::PixelPerfectCamera,
+            &draw2d::Text::unit(
+                &**self.geng.default_font(),
+                format!(
+                    "Snap: grid {} (G), angle {} (R), edges {} (E)",
+                    toggle(self.snapping.grid),
+                    toggle(self.snapping.angle),
+                    toggle(self.snapping.edges),
+                ),
+                Rgba::WHITE,
+            )
+            .scale_uniform(20.0)
+            .align_bounding_box(vec2(1.0, 1.0))
+            .translate(vec2(0.95, 0.9) * framebuffer_size),
+        );
     }
 
     fn update(&mut self, delta_time: f64) {
@@ -333,35 +648,81 @@ impl geng::State for Editor {
         let pressed = |keys: &[geng::Key]| keys.iter().any(|key| window.is_key_pressed(*key));
 
         let mut camera_move = vec2::ZERO;
-        if pressed(&[geng::Key::W]) {
-            camera_move.y += 1.0;
-        }
-        if pressed(&[geng::Key::S]) {
-            camera_move.y -= 1.0;
+        // The keys are shortcuts while Ctrl is held, like Ctrl+D to duplicate
+        if !pressed(&[geng::Key::LCtrl, geng::Key::RCtrl]) {
+            if pressed(&[geng::Key::W]) {
+                camera_move.y += 1.0;
+            }
+            if pressed(&[geng::Key::S]) {
+                camera_move.y -= 1.0;
+            }
+            if pressed(&[geng::Key::A]) {
+                camera_move.x -= 1.0;
+            }
+            if pressed(&[geng::Key::D]) {
+                camera_move.x += 1.0;
+            }
         }
-        if pressed(&[geng::Key::A]) {
-            camera_move.x -= 1.0;
+
+        // Cross the screen height in a second
+        let speed = self.world.camera.fov;
+        self.world.camera.center += camera_move * speed * delta_time;
+
+        if self.preview {
+            self.world.obstacles_movement(Time::new(delta_time));
         }
-        if pressed(&[geng::Key::D]) {
-            camera_move.x += 1.0;
+
+        self.autosave_timer -= Time::new(delta_time);
+        if self.autosave_timer < Time::ZERO {
+            self.autosave();
         }
 
-        let speed = 20.0;
-        self.world.camera.center += camera_move * speed * delta_time;
+        if let Some(action) = self.ui_action.take() {
+            self.dialog_action(action);
+        }
     }
 
     fn handle_event(&mut self, event: geng::Event) {
         let ctrl = self.geng.window().is_key_pressed(geng::Key::LCtrl);
+        let shift = self.geng.window().is_key_pressed(geng::Key::LShift);
+        if self.dialog.is_some() {
+            // The dialog captures the keyboard, and the mouse is handled by the ui
+            if let geng::Event::KeyDown { key } = event {
+                self.dialog_key(key);
+            }
+            return;
+        }
         match event {
             geng::Event::KeyDown { key } => match key {
                 geng::Key::F2 => {
                     self.draw_hitboxes = !self.draw_hitboxes;
                 }
+                geng::Key::F5 => {
+                    self.playtest(shift.then_some(self.cursor_pos));
+                }
+                geng::Key::C if ctrl => {
+                    self.copy();
+                }
+                geng::Key::V if ctrl => {
+                    self.paste(self.cursor_pos);
+                }
+                geng::Key::D if ctrl => {
+                    self.duplicate();
+                }
+                geng::Key::Delete => {
+                    self.remove(self.selection.clone());
+                }
+                geng::Key::S if ctrl && shift => {
+                    self.save_as_dialog();
+                }
                 geng::Key::S if ctrl => {
                     let _ = util::report_err(self.save());
                 }
                 geng::Key::L if ctrl => {
-                    let _ = util::report_err(self.load());
+                    self.request(FileAction::Open(self.level_path.clone()));
+                }
+                geng::Key::O if ctrl => {
+                    self.open_dialog();
                 }
                 geng::Key::Num1 => {
                     self.mode = EditorMode::Spawn;
@@ -377,12 +738,64 @@ impl geng::State for Editor {
                 }
                 geng::Key::Num5 => {
                     let prop = if let EditorMode::Prop(prop) = self.mode {
-                        (prop + 1) % self.props.len()
+                        (prop + 1) % self.assets.sprites.props.configs().len()
                     } else {
                         0
                     };
                     self.mode = EditorMode::Prop(prop);
                 }
+                geng::Key::Num6 => {
+                    self.mode = EditorMode::Path;
+                }
+                geng::Key::Num7 => {
+                    self.mode = EditorMode::Light;
+                }
+                geng::Key::Num8 => {
+                    self.mode = EditorMode::Road;
+                }
+                geng::Key::Num9 => {
+                    self.mode = EditorMode::HidingZone;
+                }
+                geng::Key::Num0 => {
+                    let item = if let EditorMode::Pickup(item) = self.mode {
+                        let i = ItemKind::ALL.iter().position(|&kind| kind == item);
+                        ItemKind::ALL[i.map_or(0, |i| (i + 1) % ItemKind::ALL.len())]
+                    } else {
+                        ItemKind::ALL[0]
+                    };
+                    self.mode = EditorMode::Pickup(item);
+                }
+                geng::Key::P => {
+                    self.toggle_preview();
+                }
+                geng::Key::F => {
+                    self.frame_selection();
+                }
+                geng::Key::H => {
+                    self.frame_level();
+                }
+                geng::Key::M => {
+                    self.show_minimap = !self.show_minimap;
+                }
+                geng::Key::G if ctrl => {
+                    self.generate_dialog();
+                }
+                geng::Key::G => {
+                    self.snapping.grid = !self.snapping.grid;
+                }
+                geng::Key::R => {
+                    self.snapping.angle = !self.snapping.angle;
+                }
+                geng::Key::E => {
+                    self.snapping.edges = !self.snapping.edges;
+                }
+                geng::Key::Escape => {
+                    if self.selection.is_empty() {
+                        self.request(FileAction::Quit);
+                    } else {
+                        self.selection.clear();
+                    }
+                }
                 _ => {}
             },
             geng::Event::MouseDown { position, button } => {
@@ -392,24 +805,34 @@ impl geng::State for Editor {
                 self.update_cursor(position);
             }
             geng::Event::MouseUp {
-                button: MouseButton::Left,
+                button: MouseButton::Left | MouseButton::Middle | MouseButton::Right,
                 ..
             } => {
                 self.release();
             }
+            geng::Event::Wheel { delta } => {
+                self.zoom(delta, self.cursor_screen);
+            }
             _ => {}
         }
     }
+
+    fn ui<'a>(&'a mut self, cx: &'a geng::ui::Controller) -> Box<dyn geng::ui::Widget + 'a> {
+        self.ui(cx)
+    }
+
+    fn transition(&mut self) -> Option<geng::state::Transition> {
+        self.transition.take()
+    }
 }
 
-pub fn run(geng: &Geng) -> impl Future<Output = impl geng::State> {
+pub fn run(geng: &Geng, level_path: PathBuf) -> impl Future<Output = impl geng::State> {
     let geng = geng.clone();
     async move {
         let assets: Assets = geng::Load::load(geng.asset_manager(), &run_dir().join("assets"))
             .await
             .expect("Failed to load assets");
 
-        let level_path = run_dir().join("assets").join("level.json");
         let level: model::Level = file::load_json(&level_path)
             .await
             .expect("Failed to load level");
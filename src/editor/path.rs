@@ -0,0 +1,145 @@
+use super::*;
+
+/// Radius around a path point that can be grabbed with the cursor.
+pub(super) const POINT_RADIUS: f32 = 0.3;
+
+impl Editor {
+    pub(super) fn path_click(&mut self, world_pos: vec2<Coord>, button: MouseButton) {
+        let Some(obstacle) = self.selected_obstacle() else {
+            if let MouseButton::Left = button {
+                self.select_obstacle(world_pos);
+            }
+            return;
+        };
+
+        match button {
+            MouseButton::Left => {
+                if let Some(point) = self.find_path_point(obstacle, world_pos) {
+                    self.drag = Some(Drag {
+                        from: world_pos,
+                        target: DragTarget::PathPoint { obstacle, point },
+                    });
+                    return;
+                }
+
+                let shift = self.geng.window().is_key_pressed(geng::Key::LShift);
+                if shift {
+                    if let Some(segment) = self.find_path_segment(obstacle, world_pos) {
+                        let path = self.path_mut(obstacle);
+                        let point = segment + 1;
+                        path.points.insert(point, world_pos);
+                        self.drag = Some(Drag {
+                            from: world_pos,
+                            target: DragTarget::PathPoint { obstacle, point },
+                        });
+                        return;
+                    }
+                }
+
+                if let Some(Entity::Obstacle(id)) = self.find_target(world_pos) {
+                    if id != obstacle {
+                        self.selection = vec![Entity::Obstacle(id)];
+                        return;
+                    }
+                }
+
+                let path = self.path_mut(obstacle);
+                path.points.push(world_pos);
+                let point = path.points.len() - 1;
+                self.drag = Some(Drag {
+                    from: world_pos,
+                    target: DragTarget::PathPoint { obstacle, point },
+                });
+            }
+            MouseButton::Right => {
+                if let Some(point) = self.find_path_point(obstacle, world_pos) {
+                    self.mark_dirty();
+                    let path = self.world.level.obstacles.path.get_mut(obstacle).unwrap();
+                    let empty = path.as_mut().map_or(false, |path| {
+                        path.points.remove(point);
+                        path.next_point = 0;
+                        path.points.is_empty()
+                    });
+                    if empty {
+                        *path = None;
+                        // The obstacle becomes static
+                        self.render_cache.invalidate(obstacle);
+                    }
+                } else {
+                    self.selection.clear();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(super) fn select_obstacle(&mut self, world_pos: vec2<Coord>) {
+        if let Some(Entity::Obstacle(id)) = self.find_target(world_pos) {
+            self.selection = vec![Entity::Obstacle(id)];
+        }
+    }
+
+    /// Returns the path of the obstacle, creating an empty one if there is none.
+    fn path_mut(&mut self, obstacle: usize) -> &mut Path {
+        // The obstacle becomes dynamic
+        self.render_cache.invalidate(obstacle);
+        self.mark_dirty();
+        self.world
+            .level
+            .obstacles
+            .path
+            .get_mut(obstacle)
+            .unwrap()
+            .get_or_insert_with(default)
+    }
+
+    pub(super) fn selected_path(&self) -> Option<&Path> {
+        let obstacle = self.selected_obstacle()?;
+        self.world.level.obstacles.path.get(obstacle)?.as_ref()
+    }
+
+    fn find_path_point(&self, obstacle: usize, position: vec2<Coord>) -> Option<usize> {
+        let path = self.world.level.obstacles.path.get(obstacle)?.as_ref()?;
+        path.points
+            .as_slice()
+            .iter()
+            .position(|&point| (point - position).len().as_f32() < POINT_RADIUS)
+    }
+
+    /// Finds the segment of the closed path under the cursor.
+    /// Returns the index of the first point of the segment.
+    fn find_path_segment(&self, obstacle: usize, position: vec2<Coord>) -> Option<usize> {
+        let path = self.world.level.obstacles.path.get(obstacle)?.as_ref()?;
+        let points = path.points.as_slice();
+        if points.len() < 2 {
+            return None;
+        }
+        let position = position.map(Coord::as_f32);
+        (0..points.len())
+            .map(|i| {
+                let a = points[i].map(Coord::as_f32);
+                let b = points[(i + 1) % points.len()].map(Coord::as_f32);
+                (i, distance_to_segment(position, a, b))
+            })
+            .filter(|&(_, distance)| distance < POINT_RADIUS)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+    }
+
+    pub(super) fn toggle_preview(&mut self) {
+        self.preview = !self.preview;
+        if self.preview {
+            self.world.obstacles = self.world.level.obstacles.clone();
+        }
+    }
+}
+
+fn distance_to_segment(point: vec2<f32>, a: vec2<f32>, b: vec2<f32>) -> f32 {
+    let ab = b - a;
+    let len_sqr = ab.len_sqr();
+    if len_sqr == 0.0 {
+        return (point - a).len();
+    }
+    let t = (vec2::dot(point - a, ab) / len_sqr).clamp(0.0, 1.0);
+    (point - (a + ab * t)).len()
+}
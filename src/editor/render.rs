@@ -1,5 +1,7 @@
 use crate::render::{draw_collider, LightsRender, WorldRender};
 
+use super::{lights::HANDLE_RADIUS, path::POINT_RADIUS, transform::TRANSFORM_HANDLE_RADIUS};
+
 use super::*;
 
 pub struct EditorRender {
@@ -18,7 +20,10 @@ impl EditorRender {
 
 impl Editor {
     pub fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
-        self.world.obstacles = self.world.level.obstacles.clone();
+        if !self.preview {
+            self.world.obstacles = self.world.level.obstacles.clone();
+        }
+        self.render_cache.update(&self.world, &self.geng);
 
         // Lighting
         let (mut world_framebuffer, mut normal_framebuffer) =
@@ -75,15 +80,17 @@ impl Editor {
             }
             EditorMode::Obstacle => {}
             EditorMode::Lamp => {}
+            EditorMode::HidingZone => {}
+            EditorMode::Pickup(_) => {}
             EditorMode::Prop(prop) => {
-                if let Some(prop) = self.props.get(prop) {
+                if let Some(config) = self.assets.sprites.props.configs().get(prop) {
                     let framebuffer_size = framebuffer.size().map(|x| x as f32);
                     self.geng.draw2d().draw2d(
                         framebuffer,
                         &geng::PixelPerfectCamera,
                         &draw2d::Text::unit(
                             &**self.geng.default_font(),
-                            format!("Prop: {prop}"),
+                            format!("Prop: {}/{}", config.category, config.name),
                             Rgba::WHITE,
                         )
                         .scale_uniform(20.0)
@@ -92,12 +99,27 @@ impl Editor {
                     );
                 }
             }
+            EditorMode::Path => self.draw_selected_path(framebuffer),
+            EditorMode::Light => self.draw_light_gizmos(framebuffer),
+            EditorMode::Road => {
+                let cell = RoadGrid::cell_at(self.cursor_pos);
+                draw_collider(
+                    &Collider::new(RoadGrid::cell_bounds(cell)),
+                    Rgba::new(1.0, 1.0, 1.0, 0.2),
+                    &self.geng,
+                    framebuffer,
+                    &self.world.camera,
+                );
+            }
         }
 
+        self.draw_transform_handles(framebuffer);
+
         if let Some(drag) = &self.drag {
+            let cursor_pos = self.snap_point(self.cursor_pos, &[]);
             match drag.target {
                 DragTarget::NewObstacle => {
-                    let aabb = Aabb2::from_corners(drag.from, self.cursor_pos);
+                    let aabb = Aabb2::from_corners(drag.from, cursor_pos);
                     let collider = Collider::new(aabb);
                     draw_collider(
                         &collider,
@@ -107,18 +129,262 @@ impl Editor {
                         &self.world.camera,
                     );
                 }
+                DragTarget::NewHidingZone => {
+                    let aabb = Aabb2::from_corners(drag.from, cursor_pos);
+                    draw_collider(
+                        &Collider::new(aabb),
+                        Rgba::new(0.1, 0.4, 0.2, 0.5),
+                        &self.geng,
+                        framebuffer,
+                        &self.world.camera,
+                    );
+                }
                 DragTarget::NewProp(prop) => {
-                    let prop = self.props.get(prop).unwrap();
-                    let texture = self.assets.sprites.props.get(prop).unwrap();
-                    let aabb = Aabb2::from_corners(drag.from, self.cursor_pos).map(Coord::as_f32);
+                    let props = &self.assets.sprites.props;
+                    let texture = props.get(&props.configs()[prop].name).unwrap();
+                    let aabb = Aabb2::from_corners(drag.from, cursor_pos).map(Coord::as_f32);
                     self.geng.draw2d().draw2d(
                         framebuffer,
                         &self.world.camera,
                         &draw2d::TexturedQuad::new(aabb, texture.texture()),
                     );
                 }
+                DragTarget::BoxSelect => {
+                    let aabb = Aabb2::from_corners(drag.from, self.cursor_pos);
+                    draw_collider(
+                        &Collider::new(aabb),
+                        Rgba::new(1.0, 1.0, 0.0, 0.2),
+                        &self.geng,
+                        framebuffer,
+                        &self.world.camera,
+                    );
+                }
                 _ => (),
             }
         }
+
+        self.draw_minimap(framebuffer);
+    }
+
+    fn draw_minimap(&self, framebuffer: &mut ugli::Framebuffer) {
+        let Some(minimap) = self.minimap() else {
+            return;
+        };
+        let camera = &geng::PixelPerfectCamera;
+        self.geng.draw2d().draw2d(
+            framebuffer,
+            camera,
+            &draw2d::Quad::new(minimap.screen, Rgba::new(0.0, 0.0, 0.0, 0.7)),
+        );
+
+        for (entity, collider) in self.entities() {
+            let color = match entity {
+                Entity::Spawn => Rgba::GREEN,
+                Entity::Waypoint(_) => Rgba::CYAN,
+                Entity::Obstacle(_) => Rgba::new(0.6, 0.6, 0.6, 1.0),
+                Entity::Lamp(_) => Rgba::YELLOW,
+                Entity::Prop(_) => Rgba::new(0.3, 0.3, 0.3, 1.0),
+                Entity::HidingZone(_) => Rgba::new(0.1, 0.4, 0.2, 1.0),
+                Entity::Pickup(id) => self
+                    .world
+                    .level
+                    .pickups
+                    .item
+                    .get(id)
+                    .map_or(Rgba::WHITE, |item| item.color()),
+            };
+            let vertices = collider
+                .vertices()
+                .map(|v| minimap.world_to_screen(v.map(Coord::as_f32)));
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                camera,
+                &draw2d::Polygon::new(vertices.to_vec(), color),
+            );
+        }
+
+        // The area visible on the screen
+        let framebuffer_size = self.framebuffer_size.map(|x| x as f32);
+        let view = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (0.0, 0.0)]
+            .into_iter()
+            .map(|(x, y)| {
+                let screen = vec2(x, y) * framebuffer_size;
+                let world = self.world.camera.screen_to_world(framebuffer_size, screen);
+                minimap.world_to_screen(world)
+            })
+            .collect();
+        self.geng.draw2d().draw2d(
+            framebuffer,
+            camera,
+            &draw2d::Chain::new(Chain::new(view), 2.0, Rgba::WHITE, 1),
+        );
+    }
+
+    fn draw_selected_path(&self, framebuffer: &mut ugli::Framebuffer) {
+        self.draw_selected_obstacle(framebuffer);
+        let Some(path) = self.selected_path() else {
+            return;
+        };
+        let mut points = path
+            .points
+            .iter()
+            .map(|(_, point)| point.map(Coord::as_f32))
+            .collect::<Vec<_>>();
+        if let Some(&point) = points.first() {
+            points.push(point);
+        }
+        if points.len() > 1 {
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                &self.world.camera,
+                &draw2d::Chain::new(
+                    Chain::new(points.clone()),
+                    0.1,
+                    Rgba::new(1.0, 1.0, 0.0, 0.5),
+                    2,
+                ),
+            );
+        }
+        points.pop();
+        for (i, point) in points.into_iter().enumerate() {
+            let color = if i == path.next_point {
+                Rgba::new(1.0, 0.5, 0.0, 0.8)
+            } else {
+                Rgba::new(1.0, 1.0, 0.0, 0.8)
+            };
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                &self.world.camera,
+                &draw2d::Ellipse::circle(point, POINT_RADIUS, color),
+            );
+        }
+    }
+
+    fn draw_selected_obstacle(&self, framebuffer: &mut ugli::Framebuffer) {
+        let Some(obstacle) = self.selected_obstacle() else {
+            return;
+        };
+        if let Some(collider) = self.world.level.obstacles.collider.get(obstacle) {
+            draw_collider(
+                collider,
+                Rgba::new(1.0, 1.0, 0.0, 0.3),
+                &self.geng,
+                framebuffer,
+                &self.world.camera,
+            );
+        }
+    }
+
+    fn draw_light_gizmos(&self, framebuffer: &mut ugli::Framebuffer) {
+        self.draw_selected_obstacle(framebuffer);
+        let Some(obstacle) = self.selected_obstacle() else {
+            return;
+        };
+
+        let camera = &self.world.camera;
+        for gizmo in self.light_gizmos(obstacle) {
+            let light = gizmo.light;
+            let position = gizmo.position;
+            let distance = light.max_distance.as_f32();
+
+            // Light cone
+            let resolution = 16;
+            let arc = (0..=resolution).map(|i| {
+                let t = i as f32 / resolution as f32;
+                let angle = light.angle + light.angle_range * (t - 0.5);
+                position + Angle::new_radians(angle).unit_direction() * distance
+            });
+            let cone: Vec<_> = if light.angle_range < f32::PI * 2.0 {
+                std::iter::once(position)
+                    .chain(arc)
+                    .chain(std::iter::once(position))
+                    .collect()
+            } else {
+                arc.collect()
+            };
+            let color = Rgba {
+                a: 0.5,
+                ..light.color
+            };
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                camera,
+                &draw2d::Chain::new(Chain::new(cone), 0.05, color, 1),
+            );
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                camera,
+                &draw2d::Segment::new(Segment(position, gizmo.direction), 0.05, color),
+            );
+
+            // Handles
+            let handles = std::iter::once((gizmo.position, Rgba::new(1.0, 1.0, 0.0, 0.8)))
+                .chain(std::iter::once((
+                    gizmo.direction,
+                    Rgba::new(1.0, 0.5, 0.0, 0.8),
+                )))
+                .chain(
+                    gizmo
+                        .range
+                        .into_iter()
+                        .map(|pos| (pos, Rgba::new(0.0, 1.0, 1.0, 0.8))),
+                );
+            for (pos, color) in handles {
+                self.geng.draw2d().draw2d(
+                    framebuffer,
+                    camera,
+                    &draw2d::Ellipse::circle(pos, HANDLE_RADIUS, color),
+                );
+            }
+        }
+    }
+
+    fn draw_transform_handles(&self, framebuffer: &mut ugli::Framebuffer) {
+        for &entity in &self.selection {
+            if let Some(collider) = self.entity_collider(entity) {
+                let vertices = collider.vertices().map(|v| v.map(Coord::as_f32));
+                let mut outline = vertices.to_vec();
+                outline.push(vertices[0]);
+                self.geng.draw2d().draw2d(
+                    framebuffer,
+                    &self.world.camera,
+                    &draw2d::Chain::new(
+                        Chain::new(outline),
+                        0.05,
+                        Rgba::new(1.0, 1.0, 0.0, 0.8),
+                        1,
+                    ),
+                );
+            }
+        }
+
+        let Some(handles) = self.transform_handles() else {
+            return;
+        };
+        let top = handles.corners[2] + (handles.corners[3] - handles.corners[2]) / 2.0;
+        self.geng.draw2d().draw2d(
+            framebuffer,
+            &self.world.camera,
+            &draw2d::Segment::new(
+                Segment(top, handles.rotate),
+                0.05,
+                Rgba::new(0.0, 1.0, 1.0, 0.8),
+            ),
+        );
+        let handles = handles
+            .corners
+            .into_iter()
+            .map(|pos| (pos, Rgba::new(1.0, 1.0, 0.0, 0.8)))
+            .chain(std::iter::once((
+                handles.rotate,
+                Rgba::new(0.0, 1.0, 1.0, 0.8),
+            )));
+        for (pos, color) in handles {
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                &self.world.camera,
+                &draw2d::Ellipse::circle(pos, TRANSFORM_HANDLE_RADIUS, color),
+            );
+        }
     }
 }
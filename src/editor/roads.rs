@@ -0,0 +1,52 @@
+use super::*;
+
+impl Editor {
+    pub(super) fn road_click(&mut self, world_pos: vec2<Coord>, button: MouseButton) {
+        let erase = match button {
+            MouseButton::Left => false,
+            MouseButton::Right => true,
+            _ => return,
+        };
+        self.drag = Some(Drag {
+            from: world_pos,
+            target: DragTarget::Road { erase },
+        });
+        self.paint_road(world_pos, erase);
+    }
+
+    /// Adds or removes the road cell at the position and retiles its neighbours.
+    /// The other props are kept in place, so that their order and ids do not change.
+    pub(super) fn paint_road(&mut self, position: vec2<Coord>, erase: bool) {
+        let cell = RoadGrid::cell_at(position);
+        let (mut grid, ids) = RoadGrid::from_props(&self.world.level.props);
+        let changed = if erase {
+            grid.remove(cell)
+        } else {
+            grid.insert(cell)
+        };
+        if !changed {
+            return;
+        }
+        self.mark_dirty();
+
+        let props = &mut self.world.level.props;
+        for neighbour in NEIGHBOURS.map(|delta| cell + delta) {
+            let Some(&id) = ids.get(&neighbour) else {
+                continue;
+            };
+            let tile = grid.prop(neighbour);
+            *props.collider.get_mut(id).unwrap() = tile.collider;
+            *props.prop.get_mut(id).unwrap() = tile.prop;
+        }
+        if erase {
+            if let Some(&id) = ids.get(&cell) {
+                props.remove(id);
+                // Ids of the props after it have shifted
+                self.selection.clear();
+            }
+        } else {
+            props.insert(grid.prop(cell));
+        }
+        self.render_cache.invalidate_props();
+    }
+}
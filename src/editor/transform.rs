@@ -0,0 +1,300 @@
+use super::*;
+
+/// Radius of the transform handles that can be grabbed with the cursor.
+pub(super) const TRANSFORM_HANDLE_RADIUS: f32 = 0.2;
+/// Distance from the top edge of the collider to the rotation handle.
+const ROTATE_HANDLE_OFFSET: f32 = 0.7;
+const MIN_SIZE: f32 = 0.1;
+
+const GRID_SIZE: f32 = 0.5;
+const ANGLE_SNAP_DEGREES: f32 = 15.0;
+/// Maximum distance between the edges to snap them together.
+const EDGE_SNAP_DISTANCE: f32 = 0.3;
+/// Obstacles further than that are not considered neighbours for edge snapping.
+const EDGE_NEIGHBOUR_DISTANCE: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Snapping {
+    pub grid: bool,
+    pub angle: bool,
+    pub edges: bool,
+}
+
+/// Transform handles of the selected entity in world coordinates.
+pub(super) struct TransformHandles {
+    /// Resize handles in the same order as [Collider::vertices].
+    pub corners: [vec2<f32>; 4],
+    pub rotate: vec2<f32>,
+}
+
+impl Default for Snapping {
+    fn default() -> Self {
+        Self {
+            grid: false,
+            angle: true,
+            edges: true,
+        }
+    }
+}
+
+impl Editor {
+    pub(super) fn entity_collider(&self, entity: Entity) -> Option<Collider> {
+        match entity {
+            Entity::Spawn => {
+                let mut collider = self.world.player.collider;
+                collider.teleport(self.world.level.spawn_point);
+                Some(collider)
+            }
+            Entity::Waypoint(id) => self.world.level.waypoints.collider.get(id).copied(),
+            Entity::Obstacle(id) => self.world.level.obstacles.collider.get(id).copied(),
+            Entity::Lamp(id) => self.world.level.lamps.collider.get(id).copied(),
+            Entity::Prop(id) => self.world.level.props.collider.get(id).copied(),
+            Entity::HidingZone(id) => self.world.level.hiding_zones.collider.get(id).copied(),
+            Entity::Pickup(id) => self.world.level.pickups.collider.get(id).copied(),
+        }
+    }
+
+    /// Returns the collider of the entity that can be transformed.
+    /// The spawn point does not have its own collider, so it can only be moved.
+    fn entity_collider_mut(&mut self, entity: Entity) -> Option<&mut Collider> {
+        let level = &mut self.world.level;
+        match entity {
+            Entity::Spawn => None,
+            Entity::Waypoint(id) => level.waypoints.collider.get_mut(id),
+            Entity::Obstacle(id) => level.obstacles.collider.get_mut(id),
+            Entity::Lamp(id) => level.lamps.collider.get_mut(id),
+            Entity::Prop(id) => level.props.collider.get_mut(id),
+            Entity::HidingZone(id) => level.hiding_zones.collider.get_mut(id),
+            Entity::Pickup(id) => level.pickups.collider.get_mut(id),
+        }
+    }
+
+    /// Updates the cached geometry after the collider of the entity has changed.
+    fn collider_changed(&mut self, entity: Entity) {
+        match entity {
+            Entity::Obstacle(id) => self.render_cache.invalidate(id),
+            Entity::Prop(_) => self.render_cache.invalidate_props(),
+            _ => {}
+        }
+        self.mark_dirty();
+    }
+
+    /// Moves the selected entities by the delta, applying the snapping to the first one.
+    /// Returns the delta that was actually applied.
+    pub(super) fn move_selection(&mut self, delta: vec2<Coord>) -> vec2<Coord> {
+        let Some(mut collider) = self
+            .selection
+            .first()
+            .and_then(|&entity| self.entity_collider(entity))
+        else {
+            return vec2::ZERO;
+        };
+        collider.translate(delta);
+        let delta = delta + self.snap_collider(&collider, &self.selection);
+
+        for entity in self.selection.clone() {
+            self.translate_entity(entity, delta);
+        }
+        delta
+    }
+
+    fn translate_entity(&mut self, entity: Entity, delta: vec2<Coord>) {
+        match entity {
+            Entity::Spawn => {
+                self.world.level.spawn_point += delta;
+                self.mark_dirty();
+            }
+            Entity::Obstacle(id) => {
+                self.render_cache.invalidate(id);
+                self.mark_dirty();
+                self.world
+                    .level
+                    .obstacles
+                    .collider
+                    .get_mut(id)
+                    .unwrap()
+                    .translate(delta);
+                // Move the path together with the obstacle
+                if let Some(Some(path)) = self.world.level.obstacles.path.get_mut(id) {
+                    for point in &mut path.points {
+                        *point += delta;
+                    }
+                }
+            }
+            _ => {
+                if let Some(collider) = self.entity_collider_mut(entity) {
+                    collider.translate(delta);
+                    self.collider_changed(entity);
+                }
+            }
+        }
+    }
+
+    /// Moves the corner of the entity to the position, keeping the opposite corner in place.
+    pub(super) fn resize_entity(&mut self, entity: Entity, corner: usize, position: vec2<Coord>) {
+        let Some(collider) = self.entity_collider(entity) else {
+            return;
+        };
+        let rotation = collider.rotation.as_radians();
+        let center = collider.pos().map(Coord::as_f32);
+        let anchor = collider.vertices()[(corner + 2) % 4].map(Coord::as_f32);
+
+        let position = if rotation == 0.0 {
+            self.snap_point(position, &[entity])
+        } else {
+            position
+        };
+        let position = position.map(Coord::as_f32);
+
+        let to_local = |p: vec2<f32>| (p - center).rotate(-rotation);
+        let local_anchor = to_local(anchor);
+        let delta = to_local(position) - local_anchor;
+
+        let mut size = delta.map(f32::abs);
+        if self.snapping.grid && rotation != 0.0 {
+            size = size.map(snap_to_grid);
+        }
+        let size = size.map(|x| x.max(MIN_SIZE));
+
+        let direction = delta.map(|x| if x < 0.0 { -1.0 } else { 1.0 });
+        let local_center = local_anchor + vec2(direction.x * size.x, direction.y * size.y) / 2.0;
+        let new_center = local_center.rotate(rotation) + center;
+
+        if let Some(collider) = self.entity_collider_mut(entity) {
+            collider.resize(size.map(Coord::new));
+            collider.teleport(new_center.map(Coord::new));
+            self.collider_changed(entity);
+        }
+    }
+
+    /// Rotates the entity so that the rotation handle points towards the position.
+    pub(super) fn rotate_entity(&mut self, entity: Entity, position: vec2<Coord>) {
+        let shift = self.geng.window().is_key_pressed(geng::Key::LShift);
+        let snap = self.snapping.angle != shift;
+        let Some(collider) = self.entity_collider_mut(entity) else {
+            return;
+        };
+        let delta = position - collider.pos();
+        if delta == vec2::ZERO {
+            return;
+        }
+        let mut angle = delta.arg().as_f32() - f32::PI / 2.0;
+        if snap {
+            let step = degrees_to_radians(ANGLE_SNAP_DEGREES);
+            angle = (angle / step).round() * step;
+        }
+        collider.rotation = Angle::new_radians(angle);
+        self.collider_changed(entity);
+    }
+
+    /// Snaps the point to the grid and to the edges of the obstacles.
+    pub(super) fn snap_point(&self, point: vec2<Coord>, ignore: &[Entity]) -> vec2<Coord> {
+        let collider = Collider::new(Aabb2::point(point));
+        point + self.snap_collider(&collider, ignore)
+    }
+
+    /// Returns the offset that snaps the bounding box of the collider
+    /// to the grid and to the edges of the neighbouring obstacles.
+    fn snap_collider(&self, collider: &Collider, ignore: &[Entity]) -> vec2<Coord> {
+        let bounds = collider.bounding_box().map(Coord::as_f32);
+        let mut offset = vec2::ZERO;
+
+        if self.snapping.grid {
+            offset = bounds.min.map(snap_to_grid) - bounds.min;
+        }
+
+        if self.snapping.edges {
+            let neighbours = bounds.extend_uniform(EDGE_NEIGHBOUR_DISTANCE);
+            let mut best = vec2(None::<f32>, None::<f32>);
+            for (id, obstacle) in self.world.level.obstacles.collider.iter() {
+                if ignore.contains(&Entity::Obstacle(id)) {
+                    continue;
+                }
+                let other = obstacle.bounding_box().map(Coord::as_f32);
+                if !other.intersects(&neighbours) {
+                    continue;
+                }
+                snap_edges(
+                    &mut best.x,
+                    bounds.min.x,
+                    bounds.max.x,
+                    other.min.x,
+                    other.max.x,
+                );
+                snap_edges(
+                    &mut best.y,
+                    bounds.min.y,
+                    bounds.max.y,
+                    other.min.y,
+                    other.max.y,
+                );
+            }
+            if let Some(x) = best.x {
+                offset.x = x;
+            }
+            if let Some(y) = best.y {
+                offset.y = y;
+            }
+        }
+
+        offset.map(Coord::new)
+    }
+
+    pub(super) fn transform_handles(&self) -> Option<TransformHandles> {
+        if let EditorMode::Path | EditorMode::Light | EditorMode::Road = self.mode {
+            return None;
+        }
+        let &[entity] = self.selection.as_slice() else {
+            return None;
+        };
+        if let Entity::Spawn = entity {
+            return None;
+        }
+        let collider = self.entity_collider(entity)?;
+        let rotation = collider.rotation.as_radians();
+        let center = collider.pos().map(Coord::as_f32);
+        let height = collider.size().y.as_f32();
+        Some(TransformHandles {
+            corners: collider.vertices().map(|v| v.map(Coord::as_f32)),
+            rotate: center + vec2(0.0, height / 2.0 + ROTATE_HANDLE_OFFSET).rotate(rotation),
+        })
+    }
+
+    /// Finds the transform handle of the selected entity under the cursor.
+    pub(super) fn find_transform_handle(&self, position: vec2<Coord>) -> Option<DragTarget> {
+        let &[entity] = self.selection.as_slice() else {
+            return None;
+        };
+        let handles = self.transform_handles()?;
+        let position = position.map(Coord::as_f32);
+        let hovered = |handle: vec2<f32>| (handle - position).len() < TRANSFORM_HANDLE_RADIUS;
+        if hovered(handles.rotate) {
+            return Some(DragTarget::Rotate(entity));
+        }
+        handles
+            .corners
+            .into_iter()
+            .position(hovered)
+            .map(|corner| DragTarget::Resize { entity, corner })
+    }
+}
+
+fn snap_to_grid(value: f32) -> f32 {
+    (value / GRID_SIZE).round() * GRID_SIZE
+}
+
+/// Finds the smallest offset along one axis that aligns the edges `min` and `max`
+/// with the edges of the other object, updating `best` if it is closer.
+fn snap_edges(best: &mut Option<f32>, min: f32, max: f32, other_min: f32, other_max: f32) {
+    for (from, to) in [
+        (min, other_max),
+        (max, other_min),
+        (min, other_min),
+        (max, other_max),
+    ] {
+        let delta = to - from;
+        if delta.abs() < EDGE_SNAP_DISTANCE && best.map_or(true, |best| delta.abs() < best.abs()) {
+            *best = Some(delta);
+        }
+    }
+}
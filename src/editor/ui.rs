@@ -0,0 +1,225 @@
+use geng::ui::*;
+
+use super::*;
+
+impl Editor {
+    pub fn ui<'a>(&'a mut self, cx: &'a Controller) -> Box<dyn Widget + 'a> {
+        let framebuffer_size = self.framebuffer_size.map(|x| x as f32);
+        let font = self.geng.default_font();
+        let text_size = 20.0;
+
+        let mut stack = geng::ui::stack![];
+
+        if let EditorMode::Path = self.mode {
+            let path = self
+                .selected_obstacle()
+                .and_then(|id| self.world.level.obstacles.path.get_mut(id))
+                .and_then(|path| path.as_mut());
+            if let Some(path) = path {
+                let before = (path.move_speed, path.angular_speed);
+                let speeds = geng::ui::column![
+                    crate::ui::slider(
+                        cx,
+                        "Move Speed",
+                        &mut path.move_speed,
+                        0.0..=20.0,
+                        font.clone(),
+                        text_size,
+                    ),
+                    crate::ui::slider(
+                        cx,
+                        "Angular Speed",
+                        &mut path.angular_speed,
+                        0.0..=10.0,
+                        font.clone(),
+                        text_size,
+                    )
+                ]
+                .align(vec2(1.0, 0.0))
+                .uniform_padding(f64::from(framebuffer_size.y) * 0.05);
+                if (path.move_speed, path.angular_speed) != before {
+                    self.mark_dirty();
+                }
+                stack.push(speeds.boxed());
+            }
+        }
+
+        if let EditorMode::Prop(selected) = self.mode {
+            let button_size = vec2(text_size * 8.0, text_size * 1.5).map(f64::from);
+            let mut palette = geng::ui::column![];
+            let mut category = None;
+            for (i, config) in self.assets.sprites.props.configs().iter().enumerate() {
+                if category != Some(&config.category) {
+                    category = Some(&config.category);
+                    let title = geng::ui::Text::new(
+                        config.category.clone(),
+                        font.clone(),
+                        text_size,
+                        Rgba::WHITE,
+                    );
+                    palette.push(title.padding_top(f64::from(text_size) * 0.5).boxed());
+                }
+                let label = if i == selected {
+                    format!("> {}", config.name)
+                } else {
+                    config.name.clone()
+                };
+                let button = geng::ui::Button::new(cx, &label);
+                if button.was_clicked() {
+                    self.mode = EditorMode::Prop(i);
+                }
+                palette.push(button.fixed_size(button_size).boxed());
+            }
+            stack.push(
+                palette
+                    .align(vec2(0.0, 0.5))
+                    .uniform_padding(f64::from(framebuffer_size.y) * 0.05)
+                    .boxed(),
+            );
+        }
+
+        // The parameters are edited in place
+        let generator = if let Some(Dialog::Generate { config }) = &mut self.dialog {
+            Some(generator_sliders(cx, config, font.clone(), text_size))
+        } else {
+            None
+        };
+
+        if let Some(dialog) = &self.dialog {
+            let button_size = vec2(text_size * 12.0, text_size * 1.5).map(f64::from);
+            let text = |text: String| {
+                geng::ui::Text::new(text, font.clone(), text_size, Rgba::WHITE)
+                    .padding_bottom(text_size.into())
+            };
+            let mut column = geng::ui::column![];
+            let mut button = |column: &mut geng::ui::Column<'a>, label: &'a str, action| {
+                let button = geng::ui::Button::new(cx, label);
+                if button.was_clicked() {
+                    self.ui_action = Some(action);
+                }
+                column.push(button.fixed_size(button_size).boxed());
+            };
+            match dialog {
+                Dialog::Open { files } => {
+                    column.push(text("Open level".to_owned()).boxed());
+                    for (name, path) in files {
+                        button(&mut column, name, DialogAction::Open(path.clone()));
+                    }
+                }
+                Dialog::SaveAs { name } => {
+                    column.push(text(format!("Save as: {name}.json")).boxed());
+                    button(&mut column, "Save", DialogAction::Save);
+                }
+                Dialog::ConfirmOverwrite { name } => {
+                    column.push(text(format!("Replace the existing {name}.json?")).boxed());
+                    button(&mut column, "Overwrite", DialogAction::Save);
+                }
+                Dialog::ConfirmDiscard { action } => {
+                    let title = match action {
+                        FileAction::Open(path) => {
+                            format!("Discard unsaved changes and open {}?", path.display())
+                        }
+                        FileAction::Generate { .. } => {
+                            "Discard unsaved changes and generate a new level?".to_owned()
+                        }
+                        FileAction::Quit => "Discard unsaved changes and quit?".to_owned(),
+                    };
+                    column.push(text(title).boxed());
+                    button(&mut column, "Discard", DialogAction::Discard);
+                }
+                Dialog::Recover { .. } => {
+                    column.push(text("Recover the autosaved changes?".to_owned()).boxed());
+                    button(&mut column, "Recover", DialogAction::Recover);
+                    button(&mut column, "Discard recovery", DialogAction::Discard);
+                }
+                Dialog::Generate { config } => {
+                    column
+                        .push(text(format!("Generate a level with seed {}", config.seed)).boxed());
+                    if let Some(sliders) = generator {
+                        column.push(sliders.padding_bottom(text_size.into()).boxed());
+                    }
+                    button(&mut column, "Generate", DialogAction::Generate);
+                }
+            }
+            button(&mut column, "Cancel", DialogAction::Cancel);
+            stack.push(column.align(vec2(0.5, 0.5)).boxed());
+        }
+
+        stack.boxed()
+    }
+}
+
+/// Sliders for the parameters of the level generator.
+fn generator_sliders<'a>(
+    cx: &'a Controller,
+    config: &mut GeneratorConfig,
+    font: Rc<geng::Font>,
+    text_size: f32,
+) -> Box<dyn Widget + 'a> {
+    let mut grid_size = config.grid_size as f64;
+    let mut block_size = config.block_size as f64;
+    let mut waypoint_count = config.waypoint_count as f64;
+    let mut column = geng::ui::column![
+        crate::ui::slider(
+            cx,
+            "Grid Size",
+            &mut grid_size,
+            1.0..=16.0,
+            font.clone(),
+            text_size,
+        ),
+        crate::ui::slider(
+            cx,
+            "Block Size",
+            &mut block_size,
+            1.0..=8.0,
+            font.clone(),
+            text_size,
+        ),
+        crate::ui::slider(
+            cx,
+            "Lamp Density",
+            &mut config.lamp_density,
+            0.0..=1.0,
+            font.clone(),
+            text_size,
+        ),
+        crate::ui::slider(
+            cx,
+            "Traffic Density",
+            &mut config.traffic_density,
+            0.0..=1.0,
+            font.clone(),
+            text_size,
+        ),
+        crate::ui::slider(
+            cx,
+            "Waypoints",
+            &mut waypoint_count,
+            2.0..=30.0,
+            font.clone(),
+            text_size,
+        ),
+    ];
+    config.grid_size = grid_size.round() as usize;
+    config.block_size = block_size.round() as usize;
+    config.waypoint_count = waypoint_count.round() as usize;
+
+    for (i, tier) in config.difficulty_tiers.iter_mut().enumerate() {
+        let mut score = *tier as f64;
+        column.push(
+            crate::ui::slider(
+                cx,
+                format!("Difficulty Tier {}", i + 1),
+                &mut score,
+                0.0..=10000.0,
+                font.clone(),
+                text_size,
+            )
+            .boxed(),
+        );
+        // Round to the hundreds
+        *tier = (score / 100.0).round() as Score * 100;
+    }
+    column.boxed()
+}
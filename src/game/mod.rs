@@ -1,6 +1,6 @@
 use crate::{
     model::{Coord, Level, PlayerControl, Time, World},
-    render::{GameRender, RenderCache},
+    render::{GameRender, MinimapRender, RenderCache},
 };
 
 use super::*;
@@ -11,12 +11,17 @@ const KEYS_ACC: [geng::Key; 2] = [geng::Key::W, geng::Key::Up];
 const KEYS_DEC: [geng::Key; 2] = [geng::Key::S, geng::Key::Down];
 const KEYS_LEFT: [geng::Key; 2] = [geng::Key::A, geng::Key::Left];
 const KEYS_RIGHT: [geng::Key; 2] = [geng::Key::D, geng::Key::Right];
+const KEYS_BOOST: [geng::Key; 2] = [geng::Key::LShift, geng::Key::RShift];
+const KEYS_BRAKE: [geng::Key; 1] = [geng::Key::Space];
 
 pub struct Game {
     geng: Geng,
     assets: Rc<Assets>,
     render: GameRender,
     render_cache: RenderCache,
+    minimap: MinimapRender,
+    /// Whether the minimap turns with the player instead of keeping north up.
+    minimap_rotate: bool,
     framebuffer_size: vec2<usize>,
     world: World,
     level: Level,
@@ -26,6 +31,13 @@ pub struct Game {
     music: geng::SoundEffect,
     master_volume: f64,
     music_volume: f64,
+    /// Whether the game was launched from the editor.
+    playtest: bool,
+    /// Seed of the endless mode run.
+    endless: Option<u64>,
+    /// Whether the route to the delivery avoids the lit streets.
+    dark_routes: bool,
+    transition: Option<geng::state::Transition>,
 }
 
 impl Game {
@@ -35,7 +47,9 @@ impl Game {
             geng: geng.clone(),
             assets: assets.clone(),
             render: GameRender::new(geng, assets),
-            render_cache: RenderCache::calculate(&world, geng, assets),
+            render_cache: RenderCache::calculate(&world, geng),
+            minimap: MinimapRender::new(geng),
+            minimap_rotate: false,
             framebuffer_size: vec2(1, 1),
             world,
             level,
@@ -45,18 +59,60 @@ impl Game {
             music: assets.music.play(),
             master_volume: 1.0,
             music_volume: 0.7,
+            playtest: false,
+            endless: None,
+            dark_routes: false,
+            transition: None,
+        }
+    }
+
+    /// Creates a game in the endless mode, where the city is generated around the player.
+    pub fn endless(geng: &Geng, assets: &Rc<Assets>, seed: u64) -> Self {
+        log::info!("Starting an endless run with seed {seed}");
+        let mut game = Self {
+            endless: Some(seed),
+            ..Self::new(geng, assets, Level::default())
+        };
+        game.reset();
+        game
+    }
+
+    /// Creates a game that returns to the previous state on Escape.
+    pub fn playtest(geng: &Geng, assets: &Rc<Assets>, level: Level) -> Self {
+        Self {
+            playtest: true,
+            ..Self::new(geng, assets, level)
         }
     }
 
     fn reset(&mut self) {
         self.world = World::new(&self.assets, self.level.clone());
+        self.world.prefer_dark_routes = self.dark_routes;
+        if let Some(seed) = self.endless {
+            self.world.start_endless(seed);
+            self.render_cache = RenderCache::calculate(&self.world, &self.geng);
+            self.update_chunks();
+        }
         self.reset = false;
     }
 
+    fn update_chunks(&mut self) {
+        let changes = self.world.update_chunks();
+        for chunk in changes.unloaded {
+            self.render_cache.unload_chunk(chunk, &self.geng);
+        }
+        for (chunk, level) in &changes.loaded {
+            self.render_cache
+                .load_chunk(*chunk, level, &self.assets, &self.geng);
+        }
+    }
+
     fn get_player_control(&mut self) -> PlayerControl {
         let mut control = PlayerControl {
             accelerate: Coord::ZERO,
             turn: Coord::ZERO,
+            boost: false,
+            brake: false,
         };
         let window = self.geng.window();
         let pressed = |keys: &[geng::Key]| keys.iter().any(|key| window.is_key_pressed(*key));
@@ -72,6 +128,8 @@ impl Game {
         if pressed(&KEYS_RIGHT) {
             control.turn -= Coord::ONE;
         }
+        control.boost = pressed(&KEYS_BOOST);
+        control.brake = pressed(&KEYS_BRAKE);
         control
     }
 }
@@ -86,14 +144,45 @@ impl geng::State for Game {
             &self.render_cache,
             framebuffer,
         );
+        if self.world.death_time.is_none() {
+            self.minimap
+                .draw(&self.world, self.minimap_rotate, framebuffer);
+        }
     }
 
     fn handle_event(&mut self, event: geng::Event) {
-        if let geng::Event::KeyDown { key: geng::Key::F2 } = event {
-            self.draw_hitboxes = !self.draw_hitboxes;
+        if let geng::Event::KeyDown { key } = event {
+            match key {
+                geng::Key::F2 => {
+                    self.draw_hitboxes = !self.draw_hitboxes;
+                }
+                geng::Key::Num1 => self.world.use_item(0),
+                geng::Key::Num2 => self.world.use_item(1),
+                geng::Key::Num3 => self.world.use_item(2),
+                geng::Key::L => {
+                    self.world.player.headlight = !self.world.player.headlight;
+                }
+                geng::Key::M => {
+                    self.minimap_rotate = !self.minimap_rotate;
+                }
+                geng::Key::N => {
+                    self.dark_routes = !self.dark_routes;
+                    self.world.prefer_dark_routes = self.dark_routes;
+                    self.world.route_timer = Time::ZERO;
+                }
+                geng::Key::Escape if self.playtest => {
+                    self.music.stop();
+                    self.transition = Some(geng::state::Transition::Pop);
+                }
+                _ => {}
+            }
         }
     }
 
+    fn transition(&mut self) -> Option<geng::state::Transition> {
+        self.transition.take()
+    }
+
     fn update(&mut self, delta_time: f64) {
         self.geng.audio().set_volume(self.master_volume);
         self.music.set_volume(self.music_volume);
@@ -106,6 +195,9 @@ impl geng::State for Game {
         let player_control = self.get_player_control();
         self.world
             .update(player_control, r32(self.player_visibilty), delta_time);
+        if self.endless.is_some() {
+            self.update_chunks();
+        }
     }
 
     fn ui<'a>(&'a mut self, cx: &'a geng::ui::Controller) -> Box<dyn geng::ui::Widget + 'a> {
@@ -113,17 +205,28 @@ impl geng::State for Game {
     }
 }
 
-pub fn run(geng: &Geng) -> impl Future<Output = impl geng::State> {
+pub fn run(geng: &Geng, level_path: std::path::PathBuf) -> impl Future<Output = impl geng::State> {
     let geng = geng.clone();
     async move {
         let assets: Assets = geng::Load::load(geng.asset_manager(), &run_dir().join("assets"))
             .await
             .expect("Failed to load assets");
 
-        let level: model::Level = file::load_json(run_dir().join("assets").join("level.json"))
+        let level: model::Level = file::load_json(&level_path)
             .await
             .expect("Failed to load level");
 
         Game::new(&geng, &Rc::new(assets), level)
     }
 }
+
+pub fn run_endless(geng: &Geng, seed: u64) -> impl Future<Output = impl geng::State> {
+    let geng = geng.clone();
+    async move {
+        let assets: Assets = geng::Load::load(geng.asset_manager(), &run_dir().join("assets"))
+            .await
+            .expect("Failed to load assets");
+
+        Game::endless(&geng, &Rc::new(assets), seed)
+    }
+}
@@ -31,6 +31,53 @@ impl Game {
 
         let mut stack = geng::ui::stack![volume];
 
+        if let Some(seed) = self.endless {
+            let seed = geng::ui::Text::new(
+                format!("Seed: {seed}"),
+                font.clone(),
+                text_size * 0.7,
+                Rgba::WHITE,
+            )
+            .align(vec2(0.0, 1.0))
+            .uniform_padding(f64::from(framebuffer_size.y) * 0.05);
+            stack.push(seed.boxed());
+        }
+
+        if !self.world.player.inventory.is_empty() || self.world.player.cloak > Time::ZERO {
+            let mut items: Vec<String> = self
+                .world
+                .player
+                .inventory
+                .iter()
+                .enumerate()
+                .map(|(i, item)| format!("[{}] {}", i + 1, item.name()))
+                .collect();
+            if self.world.player.cloak > Time::ZERO {
+                items.push(format!(
+                    "Cloaked: {:.0}s",
+                    self.world.player.cloak.as_f32().ceil()
+                ));
+            }
+            let items =
+                geng::ui::Text::new(items.join("  "), font.clone(), text_size * 0.7, Rgba::WHITE)
+                    .align(vec2(0.0, 1.0))
+                    .uniform_padding(f64::from(framebuffer_size.y) * 0.05)
+                    .padding_top(f64::from(framebuffer_size.y) * 0.05);
+            stack.push(items.boxed());
+        }
+
+        if self.playtest {
+            let hint = geng::ui::Text::new(
+                "Press Escape to return to the editor",
+                font.clone(),
+                text_size * 0.7,
+                Rgba::WHITE,
+            )
+            .align(vec2(1.0, 1.0))
+            .uniform_padding(f64::from(framebuffer_size.y) * 0.05);
+            stack.push(hint.boxed());
+        }
+
         let ui = if let Some(time) = self.world.death_time {
             self.death_ui(time, cx)
         } else {
@@ -83,16 +130,16 @@ impl Game {
         let font = self.geng.default_font();
 
         let color = Rgba::lerp(Rgba::GREEN, Rgba::RED, self.player_visibilty);
-        let visibility = geng::ui::Text::new(
-            format!("Visibility: {:.0}%", self.player_visibilty * 100.0),
-            font.clone(),
-            30.0,
-            color,
-        )
-        .align(vec2(0.5, 0.9))
-        .fixed_size(framebuffer_size.map(|x| x.into()) * 0.1)
-        .align(vec2(0.0, 0.0))
-        .padding_left(framebuffer_size.y as f64 * 0.1);
+        let visibility = if self.world.player.hidden {
+            "Visibility: hidden".to_owned()
+        } else {
+            format!("Visibility: {:.0}%", self.player_visibilty * 100.0)
+        };
+        let visibility = geng::ui::Text::new(visibility, font.clone(), 30.0, color)
+            .align(vec2(0.5, 0.9))
+            .fixed_size(framebuffer_size.map(|x| x.into()) * 0.1)
+            .align(vec2(0.0, 0.0))
+            .padding_left(framebuffer_size.y as f64 * 0.1);
 
         let color = Rgba::lerp(
             Rgba::RED,
@@ -110,6 +157,34 @@ impl Game {
         .align(vec2(0.0, 0.0))
         .padding_left(framebuffer_size.y as f64 * 0.1);
 
+        let color = if self.world.player.boosting {
+            Rgba::opaque(1.0, 0.6, 0.1)
+        } else {
+            Rgba::lerp(Rgba::RED, Rgba::CYAN, self.world.player.stamina)
+        };
+        let stamina = geng::ui::Text::new(
+            format!("Stamina: {:.0}%", self.world.player.stamina * 100.0),
+            font.clone(),
+            30.0,
+            color,
+        )
+        .align(vec2(0.5, 0.5))
+        .fixed_size(framebuffer_size.map(|x| x.into()) * 0.1)
+        .align(vec2(0.0, 0.0))
+        .padding_left(framebuffer_size.y as f64 * 0.1 + framebuffer_size.x as f64 * 0.12);
+
+        let color = Rgba::lerp(Rgba::GREEN, Rgba::RED, self.world.player.noise);
+        let noise = geng::ui::Text::new(
+            format!("Noise: {:.0}%", self.world.player.noise * 100.0),
+            font.clone(),
+            30.0,
+            color,
+        )
+        .align(vec2(0.5, 0.1))
+        .fixed_size(framebuffer_size.map(|x| x.into()) * 0.1)
+        .align(vec2(0.0, 0.0))
+        .padding_left(framebuffer_size.y as f64 * 0.1);
+
         let score = geng::ui::Text::new(
             format!("Score: {}", self.world.player.score),
             font.clone(),
@@ -119,6 +194,52 @@ impl Game {
         .fixed_size(framebuffer_size.map(|x| x.into()) * 0.1)
         .align(vec2(0.5, 1.0));
 
-        geng::ui::stack![visibility, health, score].boxed()
+        let color = if self.world.player.streak > 0 {
+            Rgba::opaque(0.6, 0.5, 1.0)
+        } else {
+            Rgba::WHITE
+        };
+        let streak = geng::ui::Text::new(
+            format!(
+                "x{:.1}  Streak: {}",
+                self.world.player.multiplier, self.world.player.streak
+            ),
+            font.clone(),
+            25.0,
+            color,
+        )
+        .fixed_size(framebuffer_size.map(|x| x.into()) * 0.1)
+        .align(vec2(0.5, 1.0))
+        .padding_top(framebuffer_size.y as f64 * 0.07);
+
+        let route = geng::ui::Text::new(
+            if self.dark_routes {
+                "Route: dark (N)"
+            } else {
+                "Route: shortest (N)"
+            },
+            font.clone(),
+            20.0,
+            Rgba::WHITE,
+        )
+        .fixed_size(framebuffer_size.map(|x| x.into()) * 0.1)
+        .align(vec2(0.5, 0.0));
+
+        let headlight = geng::ui::Text::new(
+            if self.world.player.headlight {
+                "Headlight: on (L)"
+            } else {
+                "Headlight: off (L)"
+            },
+            font.clone(),
+            20.0,
+            Rgba::WHITE,
+        )
+        .fixed_size(framebuffer_size.map(|x| x.into()) * 0.1)
+        .align(vec2(0.5, 0.0))
+        .padding_bottom(framebuffer_size.y as f64 * 0.03);
+
+        geng::ui::stack![visibility, health, stamina, noise, score, streak, route, headlight]
+            .boxed()
     }
 }
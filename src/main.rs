@@ -9,12 +9,52 @@ mod render;
 mod ui;
 mod util;
 
+use std::path::PathBuf;
+
 use assets::Assets;
 
 #[derive(clap::Parser)]
 struct Args {
     #[clap(long)]
     editor: bool,
+    /// Path to the level file to play or edit.
+    #[clap(long)]
+    level: Option<PathBuf>,
+    /// Play in an endless procedurally generated city.
+    #[clap(long)]
+    endless: bool,
+    /// Seed of the endless city, random if not specified.
+    #[clap(long)]
+    seed: Option<u64>,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Create an empty level and open it in the editor.
+    New {
+        path: PathBuf,
+        /// Overwrite the file if it already exists.
+        #[clap(long)]
+        force: bool,
+    },
+    /// Generate a city level.
+    Generate {
+        path: PathBuf,
+        /// Overwrite the file if it already exists.
+        #[clap(long)]
+        force: bool,
+        #[clap(flatten)]
+        config: model::GeneratorConfig,
+    },
+    /// Check the level for structural problems.
+    Validate { path: PathBuf },
+    /// Upgrade the level files to the current format in place.
+    Migrate {
+        #[clap(required = true)]
+        paths: Vec<PathBuf>,
+    },
 }
 
 fn main() {
@@ -23,14 +63,125 @@ fn main() {
     logger::init();
     geng::setup_panic_handler();
 
+    let mut editor = args.editor;
+    let mut level_path = args
+        .level
+        .unwrap_or_else(|| run_dir().join("assets").join("level.json"));
+
+    if let Some(command) = args.command {
+        match command {
+            Command::New { path, force } => {
+                if let Err(err) = create_level(&path, &model::Level::default(), force) {
+                    log::error!("Failed to create the level at {path:?}: {err:?}");
+                    std::process::exit(1);
+                }
+                editor = true;
+                level_path = path;
+            }
+            Command::Generate {
+                path,
+                force,
+                config,
+            } => {
+                let level = model::generate_level(&config);
+                if let Err(err) = create_level(&path, &level, force) {
+                    log::error!("Failed to create the level at {path:?}: {err:?}");
+                    std::process::exit(1);
+                }
+                return;
+            }
+            Command::Validate { path } => {
+                match validate_level(&path) {
+                    Ok(true) => return,
+                    Ok(false) => {}
+                    Err(err) => log::error!("Failed to validate the level at {path:?}: {err:?}"),
+                }
+                std::process::exit(1);
+            }
+            Command::Migrate { paths } => {
+                let mut failed = false;
+                for path in paths {
+                    if let Err(err) = migrate_level(&path) {
+                        log::error!("Failed to migrate the level at {path:?}: {err:?}");
+                        failed = true;
+                    }
+                }
+                if failed {
+                    std::process::exit(1);
+                }
+                return;
+            }
+        }
+    }
+
     let geng = Geng::new_with(geng::ContextOptions {
         title: "Shadow Delivery".to_string(),
         ..default()
     });
 
-    if args.editor {
-        geng.clone().run_loading(editor::run(&geng))
+    if editor {
+        geng.clone().run_loading(editor::run(&geng, level_path))
+    } else if args.endless {
+        let seed = args.seed.unwrap_or_else(|| thread_rng().gen());
+        geng.clone().run_loading(game::run_endless(&geng, seed))
     } else {
-        geng.clone().run_loading(game::run(&geng))
+        geng.clone().run_loading(game::run(&geng, level_path))
     };
 }
+
+/// Writes the level to a new file.
+fn create_level(path: &std::path::Path, level: &model::Level, force: bool) -> anyhow::Result<()> {
+    if path.exists() && !force {
+        anyhow::bail!("the file already exists, use --force to overwrite it");
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    util::write_atomic(path, level)?;
+    log::info!("Created the level at {path:?}");
+    Ok(())
+}
+
+/// Rewrites the level file in the current format.
+fn migrate_level(path: &std::path::Path) -> anyhow::Result<()> {
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let document: serde_json::Value = serde_json::from_reader(reader)?;
+    let version = model::level_version(&document)?;
+    if version == model::LEVEL_VERSION {
+        log::info!("The level at {path:?} is up to date");
+        return Ok(());
+    }
+    if version > model::LEVEL_VERSION {
+        anyhow::bail!(
+            "the level is from a newer version ({version}, supported {})",
+            model::LEVEL_VERSION
+        );
+    }
+
+    let level = model::Level::try_from(document)?;
+    util::write_atomic(path, &level)?;
+    log::info!(
+        "Migrated the level at {path:?} from version {version} to {}",
+        model::LEVEL_VERSION
+    );
+    Ok(())
+}
+
+/// Reports the problems in the level, returns whether it is valid.
+fn validate_level(path: &std::path::Path) -> anyhow::Result<bool> {
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let level: model::Level = serde_json::from_reader(reader)?;
+
+    let props = assets::Props::load_manifest(&run_dir().join("assets").join(assets::PROPS_DIR))?;
+
+    let issues = model::validate_level(&level, &props);
+    for issue in &issues {
+        println!("{}: {issue}", path.display());
+    }
+    if issues.is_empty() {
+        log::info!("The level at {path:?} is valid");
+    } else {
+        log::error!("Found {} problem(s) in the level at {path:?}", issues.len());
+    }
+    Ok(issues.is_empty())
+}
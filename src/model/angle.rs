@@ -35,6 +35,8 @@ pub fn normalize_radians(radians: f32) -> f32 {
     let mut angle = (radians / tau).fract();
     if angle > 0.5 {
         angle -= 1.0;
+    } else if angle < -0.5 {
+        angle += 1.0;
     }
     angle * tau
 }
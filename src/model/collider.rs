@@ -50,6 +50,19 @@ impl Collider {
         self.aabb.size()
     }
 
+    /// Axis-aligned bounding box of the rotated collider.
+    pub fn bounding_box(&self) -> Aabb2<Coord> {
+        let [first, rest @ ..] = self.vertices();
+        rest.into_iter().fold(Aabb2::point(first), |aabb, p| {
+            aabb_union(aabb, Aabb2::point(p))
+        })
+    }
+
+    /// Changes the size of the collider keeping its center in place.
+    pub fn resize(&mut self, size: vec2<Coord>) {
+        self.aabb = Aabb2::point(self.pos()).extend_symmetric(size / Coord::new(2.0));
+    }
+
     pub fn teleport(&mut self, position: vec2<Coord>) {
         let delta = position - self.pos();
         self.translate(delta);
@@ -88,3 +101,11 @@ impl Default for Collider {
         }
     }
 }
+
+/// Smallest box containing both boxes.
+pub fn aabb_union(a: Aabb2<Coord>, b: Aabb2<Coord>) -> Aabb2<Coord> {
+    Aabb2 {
+        min: vec2(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+        max: vec2(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+    }
+}
@@ -0,0 +1,236 @@
+use super::*;
+
+/// Chunks up to this distance from the player's chunk are loaded.
+const CHUNK_LOAD_RADIUS: i32 = 2;
+/// Chunks further than this distance from the player's chunk are unloaded.
+const CHUNK_UNLOAD_RADIUS: i32 = 3;
+
+/// State of the endless mode, where the city is generated in chunks around the player.
+pub struct Endless {
+    pub config: GeneratorConfig,
+    loaded: HashSet<vec2<i32>>,
+    /// Chunks the player has driven through.
+    visited: HashSet<vec2<i32>>,
+}
+
+/// Chunks loaded and unloaded during an update.
+#[derive(Default)]
+pub struct ChunkChanges {
+    /// The chunks with their contents.
+    pub loaded: Vec<(vec2<i32>, Level)>,
+    pub unloaded: Vec<vec2<i32>>,
+}
+
+impl Endless {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            config: GeneratorConfig { seed, ..default() },
+            loaded: HashSet::new(),
+            visited: HashSet::new(),
+        }
+    }
+
+    pub fn chunk_size(&self) -> f32 {
+        (self.config.block_size.max(1) + 1) as f32 * ROAD_CELL_SIZE
+    }
+
+    pub fn chunk_at(&self, position: vec2<Coord>) -> vec2<i32> {
+        let size = self.chunk_size();
+        position.map(|x| (x.as_f32() / size).floor() as i32)
+    }
+
+    pub fn chunk_bounds(&self, chunk: vec2<i32>) -> Aabb2<Coord> {
+        let size = self.chunk_size();
+        Aabb2::point(chunk.map(|x| x as f32 * size))
+            .extend_positive(vec2::splat(size))
+            .map(Coord::new)
+    }
+
+    /// Whether the player has already been in the chunk containing the position.
+    pub fn is_visited(&self, position: vec2<Coord>) -> bool {
+        self.visited.contains(&self.chunk_at(position))
+    }
+}
+
+impl World {
+    /// Starts an endless run from the first chunk.
+    pub fn start_endless(&mut self, seed: u64) {
+        self.level = Level::default();
+        let spawn = vec2::splat(Coord::new(ROAD_CELL_SIZE / 2.0));
+        self.level.spawn_point = spawn;
+        self.player.collider.teleport(spawn);
+        self.endless = Some(Endless::new(seed));
+    }
+
+    /// Generates the chunks around the player and unloads the ones far behind.
+    pub fn update_chunks(&mut self) -> ChunkChanges {
+        let mut changes = ChunkChanges::default();
+        let Some(endless) = &mut self.endless else {
+            return changes;
+        };
+
+        let center = endless.chunk_at(self.player.collider.pos());
+        endless.visited.insert(center);
+
+        let distance =
+            |chunk: vec2<i32>| (chunk.x - center.x).abs().max((chunk.y - center.y).abs());
+        changes.unloaded = endless
+            .loaded
+            .iter()
+            .copied()
+            .filter(|&chunk| distance(chunk) > CHUNK_UNLOAD_RADIUS)
+            .collect();
+        for chunk in &changes.unloaded {
+            endless.loaded.remove(chunk);
+        }
+
+        for x in -CHUNK_LOAD_RADIUS..=CHUNK_LOAD_RADIUS {
+            for y in -CHUNK_LOAD_RADIUS..=CHUNK_LOAD_RADIUS {
+                let chunk = center + vec2(x, y);
+                if endless.loaded.insert(chunk) {
+                    let level = generate_chunk(&endless.config, chunk);
+                    changes.loaded.push((chunk, level));
+                }
+            }
+        }
+
+        for &chunk in &changes.unloaded {
+            self.unload_chunk(chunk);
+        }
+        for (chunk, level) in &changes.loaded {
+            self.load_chunk(*chunk, level);
+        }
+        if !changes.loaded.is_empty() || !changes.unloaded.is_empty() {
+            self.nav = NavGrid::new(&self.level, &[&self.level.obstacles, &self.obstacles]);
+            self.route_timer = Time::ZERO;
+        }
+        changes
+    }
+
+    fn load_chunk(&mut self, chunk_pos: vec2<i32>, chunk: &Level) {
+        for (_, waypoint) in chunk.waypoints.iter() {
+            self.level.waypoints.insert(Waypoint {
+                collider: *waypoint.collider,
+            });
+        }
+        for (_, obstacle) in chunk.obstacles.iter() {
+            self.level.obstacles.insert(Obstacle {
+                difficulty: *obstacle.difficulty,
+                collider: *obstacle.collider,
+                lights: obstacle.lights.clone(),
+                path: obstacle.path.clone(),
+                alert: None,
+                chunk: Some(chunk_pos),
+            });
+        }
+        for (_, lamp) in chunk.lamps.iter() {
+            self.level.lamps.insert(Lamp {
+                collider: *lamp.collider,
+                light: *lamp.light,
+                state: lamp.state.clone(),
+                up_time: *lamp.up_time,
+                down_time: *lamp.down_time,
+            });
+        }
+        for (_, prop) in chunk.props.iter() {
+            self.level.props.insert(Prop {
+                collider: *prop.collider,
+                prop: prop.prop.clone(),
+            });
+        }
+        for (_, zone) in chunk.hiding_zones.iter() {
+            self.level.hiding_zones.insert(HidingZone {
+                collider: *zone.collider,
+            });
+        }
+        for (_, pickup) in chunk.pickups.iter() {
+            self.level.pickups.insert(Pickup {
+                collider: *pickup.collider,
+                item: *pickup.item,
+            });
+        }
+    }
+
+    /// Removes everything that belongs to the chunk.
+    /// Obstacles are removed by the chunk they were generated in,
+    /// since the traffic drives across the chunk borders.
+    fn unload_chunk(&mut self, chunk: vec2<i32>) {
+        let Some(endless) = &self.endless else {
+            return;
+        };
+        let area = endless.chunk_bounds(chunk);
+
+        // Keep track of the active waypoint, since the ids shift
+        let active = self
+            .level
+            .waypoints
+            .collider
+            .get(self.active_waypoint)
+            .map(|collider| collider.pos());
+
+        for id in ids_inside(self.level.waypoints.collider.iter(), area) {
+            self.level.waypoints.remove(id);
+        }
+        for id in ids_owned(self.level.obstacles.chunk.iter(), chunk) {
+            self.level.obstacles.remove(id);
+        }
+        for id in ids_owned(self.obstacles.chunk.iter(), chunk) {
+            self.obstacles.remove(id);
+        }
+        for id in ids_inside(self.level.lamps.collider.iter(), area) {
+            self.level.lamps.remove(id);
+        }
+        for id in ids_inside(self.level.props.collider.iter(), area) {
+            self.level.props.remove(id);
+        }
+        for id in ids_inside(self.level.hiding_zones.collider.iter(), area) {
+            self.level.hiding_zones.remove(id);
+        }
+        for id in ids_inside(self.level.pickups.collider.iter(), area) {
+            self.level.pickups.remove(id);
+        }
+
+        let active = active.and_then(|active| {
+            self.level
+                .waypoints
+                .collider
+                .iter()
+                .find(|(_, collider)| collider.pos() == active)
+                .map(|(id, _)| id)
+        });
+        match active {
+            Some(id) => self.active_waypoint = id,
+            None => self.next_waypoint(),
+        }
+    }
+}
+
+/// Returns the ids of the colliders inside the area from the back,
+/// so that the ids of the remaining ones stay valid during the removal.
+fn ids_inside<'a>(
+    colliders: impl Iterator<Item = (usize, &'a Collider)>,
+    area: Aabb2<Coord>,
+) -> Vec<usize> {
+    let mut ids: Vec<usize> = colliders
+        .filter(|(_, collider)| area.contains(collider.pos()))
+        .map(|(id, _)| id)
+        .collect();
+    ids.sort();
+    ids.reverse();
+    ids
+}
+
+/// Returns the ids of the obstacles owned by the chunk from the back,
+/// so that the ids of the remaining ones stay valid during the removal.
+fn ids_owned<'a>(
+    chunks: impl Iterator<Item = (usize, &'a Option<vec2<i32>>)>,
+    chunk: vec2<i32>,
+) -> Vec<usize> {
+    let mut ids: Vec<usize> = chunks
+        .filter(|(_, owner)| **owner == Some(chunk))
+        .map(|(id, _)| id)
+        .collect();
+    ids.sort();
+    ids.reverse();
+    ids
+}
@@ -0,0 +1,319 @@
+use super::*;
+
+use super::logic::{WAYPOINT_DISTANCE_MAX, WAYPOINT_DISTANCE_MIN};
+
+/// Space between the buildings and the road.
+const SIDEWALK: f32 = 0.5;
+/// Space between the buildings in the same block.
+const ALLEY: f32 = 1.0;
+const CAR_SIZE: f32 = 0.5;
+const LAMP_SIZE: f32 = 0.5;
+const WAYPOINT_SIZE: f32 = 0.5;
+/// Default scores at which the traffic appears.
+const DIFFICULTY_TIERS: [Score; 4] = [0, 1000, 3000, 5000];
+const GLOBAL_LIGHT_INTENSITY: f32 = 0.15;
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct GeneratorConfig {
+    /// Seed of the random generator.
+    #[clap(long, default_value_t = 0)]
+    pub seed: u64,
+    /// Number of blocks along each axis.
+    #[clap(long, default_value_t = 4)]
+    pub grid_size: usize,
+    /// Size of a block in road cells.
+    #[clap(long, default_value_t = 2)]
+    pub block_size: usize,
+    /// Probability of a lamp at each intersection.
+    #[clap(long, default_value_t = 0.5)]
+    pub lamp_density: f64,
+    /// Probability of a car driving around each block.
+    #[clap(long, default_value_t = 0.3)]
+    pub traffic_density: f64,
+    #[clap(long, default_value_t = 10)]
+    pub waypoint_count: usize,
+    /// Scores at which the traffic appears, one is chosen for each car.
+    #[clap(long, value_delimiter = ',', default_values_t = DIFFICULTY_TIERS)]
+    pub difficulty_tiers: Vec<Score>,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            grid_size: 4,
+            block_size: 2,
+            lamp_density: 0.5,
+            traffic_density: 0.3,
+            waypoint_count: 10,
+            difficulty_tiers: DIFFICULTY_TIERS.to_vec(),
+        }
+    }
+}
+
+/// Generates a square city grid of blocks separated by roads.
+pub fn generate_level(config: &GeneratorConfig) -> Level {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut level = Level::default();
+    level.global_light.intensity = GLOBAL_LIGHT_INTENSITY;
+
+    let grid_size = config.grid_size.max(1);
+    let block_size = config.block_size.max(1);
+    // Distance between the roads in cells
+    let period = block_size + 1;
+    let cells = grid_size * period + 1;
+    let to_world = |cell: usize| cell as f32 * ROAD_CELL_SIZE;
+    let road_center = |cell: usize| to_world(cell) + ROAD_CELL_SIZE / 2.0;
+
+    // Roads
+    let mut roads = RoadGrid::default();
+    let mut road_cells = Vec::new();
+    for x in 0..cells {
+        for y in 0..cells {
+            if x % period == 0 || y % period == 0 {
+                roads.insert(vec2(x as i32, y as i32));
+                road_cells.push(vec2(road_center(x), road_center(y)));
+            }
+        }
+    }
+    for prop in roads.props() {
+        level.props.insert(prop);
+    }
+
+    for bx in 0..grid_size {
+        for by in 0..grid_size {
+            // Buildings
+            let min = vec2(to_world(bx * period + 1), to_world(by * period + 1));
+            let block = Aabb2::point(min)
+                .extend_positive(vec2::splat(to_world(block_size)))
+                .extend_uniform(-SIDEWALK);
+            for building in split_block(block, &mut rng) {
+                level.obstacles.insert(Obstacle {
+                    collider: Collider::new(building.map(Coord::new)),
+                    ..default()
+                });
+            }
+
+            // Traffic driving around the block
+            if rng.gen_bool(config.traffic_density.clamp(0.0, 1.0)) {
+                let min = vec2(road_center(bx * period), road_center(by * period));
+                let max = vec2(
+                    road_center((bx + 1) * period),
+                    road_center((by + 1) * period),
+                );
+                level
+                    .obstacles
+                    .insert(car(&traffic_loop(min, max, &mut rng), config, &mut rng));
+            }
+        }
+    }
+
+    // Lamps on the corners of the intersections
+    for x in (0..cells).step_by(period) {
+        for y in (0..cells).step_by(period) {
+            if !rng.gen_bool(config.lamp_density.clamp(0.0, 1.0)) {
+                continue;
+            }
+            let corner = vec2(
+                if rng.gen_bool(0.5) { 1.0 } else { -1.0 },
+                if rng.gen_bool(0.5) { 1.0 } else { -1.0 },
+            );
+            let position = vec2(road_center(x), road_center(y)) + corner * ROAD_CELL_SIZE / 2.0;
+            level.lamps.insert(lamp(position, &mut rng));
+        }
+    }
+
+    // Spawn on the intersection closest to the center
+    let center = to_world(cells) / 2.0;
+    let spawn = (0..cells)
+        .step_by(period)
+        .map(road_center)
+        .min_by(|a, b| (a - center).abs().total_cmp(&(b - center).abs()))
+        .unwrap_or(center);
+    level.spawn_point = vec2(spawn, spawn).map(Coord::new);
+
+    for position in place_waypoints(road_cells, config.waypoint_count, &mut rng) {
+        let aabb = Aabb2::point(position).extend_uniform(WAYPOINT_SIZE / 2.0);
+        level.waypoints.insert(Waypoint {
+            collider: Collider::new(aabb.map(Coord::new)),
+        });
+    }
+
+    level
+}
+
+/// Generates a single chunk of an endless city.
+/// Each chunk is a block with the roads along its left and bottom sides,
+/// and the same chunk is always generated the same way for the same seed.
+pub fn generate_chunk(config: &GeneratorConfig, chunk: vec2<i32>) -> Level {
+    let mut rng = StdRng::seed_from_u64(chunk_seed(config.seed, chunk));
+    let mut level = Level::default();
+
+    let block_size = config.block_size.max(1) as i32;
+    let period = block_size + 1;
+    let origin = chunk * period;
+    let is_road =
+        |cell: vec2<i32>| cell.x.rem_euclid(period) == 0 || cell.y.rem_euclid(period) == 0;
+    let to_world = |cell: vec2<i32>| cell.map(|x| x as f32 * ROAD_CELL_SIZE);
+    let road_center = |cell: vec2<i32>| to_world(cell) + vec2::splat(ROAD_CELL_SIZE / 2.0);
+
+    // Roads, including the neighbouring cells for the tiles to connect across the chunks
+    let mut roads = RoadGrid::default();
+    let mut road_cells = Vec::new();
+    for x in -1..=period {
+        for y in -1..=period {
+            let cell = origin + vec2(x, y);
+            if !is_road(cell) {
+                continue;
+            }
+            roads.insert(cell);
+            if (0..period).contains(&x) && (0..period).contains(&y) {
+                road_cells.push(road_center(cell));
+            }
+        }
+    }
+    let bounds =
+        Aabb2::point(to_world(origin)).extend_positive(vec2::splat(period as f32 * ROAD_CELL_SIZE));
+    for prop in roads.props() {
+        if bounds.contains(prop.collider.pos().map(Coord::as_f32)) {
+            level.props.insert(prop);
+        }
+    }
+
+    let block = Aabb2::point(to_world(origin + vec2(1, 1)))
+        .extend_positive(vec2::splat(block_size as f32 * ROAD_CELL_SIZE))
+        .extend_uniform(-SIDEWALK);
+    for building in split_block(block, &mut rng) {
+        level.obstacles.insert(Obstacle {
+            collider: Collider::new(building.map(Coord::new)),
+            ..default()
+        });
+    }
+
+    if rng.gen_bool(config.traffic_density.clamp(0.0, 1.0)) {
+        let points = traffic_loop(
+            road_center(origin),
+            road_center(origin + vec2(period, period)),
+            &mut rng,
+        );
+        level.obstacles.insert(car(&points, config, &mut rng));
+    }
+
+    if rng.gen_bool(config.lamp_density.clamp(0.0, 1.0)) {
+        // On the corner of the block
+        let position = road_center(origin) + vec2::splat(ROAD_CELL_SIZE / 2.0);
+        level.lamps.insert(lamp(position, &mut rng));
+    }
+
+    if let Some(&position) = road_cells.choose(&mut rng) {
+        let aabb = Aabb2::point(position).extend_uniform(WAYPOINT_SIZE / 2.0);
+        level.waypoints.insert(Waypoint {
+            collider: Collider::new(aabb.map(Coord::new)),
+        });
+    }
+
+    level
+}
+
+/// Mixes the chunk position into the seed.
+fn chunk_seed(seed: u64, chunk: vec2<i32>) -> u64 {
+    let x = (chunk.x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    let y = (chunk.y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    seed ^ x ^ y.rotate_left(32)
+}
+
+/// Returns the corners of the road loop around the block in a random driving order.
+fn traffic_loop(min: vec2<f32>, max: vec2<f32>, rng: &mut impl Rng) -> Vec<vec2<f32>> {
+    let mut points = vec![min, vec2(max.x, min.y), max, vec2(min.x, max.y)];
+    if rng.gen_bool(0.5) {
+        points.reverse();
+    }
+    points.rotate_left(rng.gen_range(0..points.len()));
+    points
+}
+
+/// Splits the block into up to 2x2 buildings separated by alleys.
+fn split_block(block: Aabb2<f32>, rng: &mut impl Rng) -> Vec<Aabb2<f32>> {
+    let parts = vec2(rng.gen_range(1..=2), rng.gen_range(1..=2));
+    let gaps = parts.map(|x| (x - 1) as f32 * ALLEY);
+    let size = (block.size() - gaps) / parts.map(|x| x as f32);
+    let mut buildings = Vec::new();
+    for i in 0..parts.x {
+        for j in 0..parts.y {
+            let offset = vec2(i as f32 * (size.x + ALLEY), j as f32 * (size.y + ALLEY));
+            buildings.push(Aabb2::point(block.min + offset).extend_positive(size));
+        }
+    }
+    buildings
+}
+
+fn car(points: &[vec2<f32>], config: &GeneratorConfig, rng: &mut impl Rng) -> Obstacle {
+    let start = points[0];
+    Obstacle {
+        difficulty: config.difficulty_tiers.choose(rng).copied().unwrap_or(0),
+        collider: Collider::new(
+            Aabb2::point(start)
+                .extend_uniform(CAR_SIZE / 2.0)
+                .map(Coord::new),
+        ),
+        lights: vec![Spotlight {
+            position: vec2(CAR_SIZE / 2.0, 0.0).map(Coord::new),
+            angle_range: 1.2,
+            max_distance: Coord::new(8.0),
+            ..default()
+        }],
+        path: Some(Path {
+            next_point: 1,
+            angular_speed: r32(10.0),
+            points: points.iter().map(|p| p.map(Coord::new)).collect(),
+            ..default()
+        }),
+        alert: None,
+        chunk: None,
+    }
+}
+
+fn lamp(position: vec2<f32>, rng: &mut impl Rng) -> Lamp {
+    // Some of the lamps are flickering
+    let down_time = if rng.gen_bool(0.3) { 1.5 } else { 0.0 };
+    Lamp {
+        collider: Collider::new(
+            Aabb2::point(position)
+                .extend_uniform(LAMP_SIZE / 2.0)
+                .map(Coord::new),
+        ),
+        light: Spotlight {
+            angle_range: f32::PI * 2.0,
+            ..default()
+        },
+        down_time: Time::new(down_time),
+        ..default()
+    }
+}
+
+/// Picks the waypoints so that each of them has another one
+/// at a distance the game can choose as the next target.
+fn place_waypoints(
+    mut candidates: Vec<vec2<f32>>,
+    count: usize,
+    rng: &mut impl Rng,
+) -> Vec<vec2<f32>> {
+    candidates.shuffle(rng);
+    let mut placed = Vec::new();
+    placed.extend(candidates.pop());
+    while placed.len() < count {
+        let fits = |point: &vec2<f32>| {
+            let distances = placed
+                .iter()
+                .map(|other: &vec2<f32>| (*other - *point).len());
+            distances.clone().all(|d| d >= WAYPOINT_DISTANCE_MIN)
+                && distances.into_iter().any(|d| d <= WAYPOINT_DISTANCE_MAX)
+        };
+        let Some(i) = candidates.iter().position(fits) else {
+            break;
+        };
+        placed.push(candidates.swap_remove(i));
+    }
+    placed.truncate(count);
+    placed
+}
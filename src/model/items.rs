@@ -0,0 +1,184 @@
+use super::*;
+
+pub const INVENTORY_SIZE: usize = 3;
+const REPAIR_HEALTH: f32 = 40.0;
+const CLOAK_DURATION: f32 = 5.0;
+const DECOY_DURATION: f32 = 6.0;
+/// Distance at which the guards notice the decoy.
+const DECOY_RADIUS: f32 = 12.0;
+const PICKUP_SIZE: f32 = 0.5;
+/// Time between the pickups appearing around the player.
+pub(super) const PICKUP_SPAWN_INTERVAL: f32 = 20.0;
+/// No more pickups spawn while there are this many in the level.
+const PICKUPS_MAX: usize = 5;
+const PICKUP_SPAWN_DISTANCE_MIN: f32 = 5.0;
+const PICKUP_SPAWN_DISTANCE_MAX: f32 = 20.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ItemKind {
+    /// Restores some health.
+    #[default]
+    RepairKit,
+    /// Hides the player for a while.
+    Cloak,
+    /// Attracts the nearby guards.
+    Decoy,
+}
+
+/// A noise source left by the player that the guards look at.
+pub struct Decoy {
+    pub position: vec2<Coord>,
+    pub time: Time,
+}
+
+impl ItemKind {
+    pub const ALL: [Self; 3] = [Self::RepairKit, Self::Cloak, Self::Decoy];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RepairKit => "Repair kit",
+            Self::Cloak => "Shadow cloak",
+            Self::Decoy => "Decoy",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::RepairKit => Rgba::opaque(0.2, 0.9, 0.3),
+            Self::Cloak => Rgba::opaque(0.6, 0.3, 0.9),
+            Self::Decoy => Rgba::opaque(1.0, 0.6, 0.1),
+        }
+    }
+}
+
+impl World {
+    /// Collects the pickups the player drives over while there is space in the inventory.
+    pub(super) fn pickups(&mut self) {
+        #[derive(StructQuery)]
+        struct PickupRef<'a> {
+            collider: &'a Collider,
+            item: &'a ItemKind,
+        }
+
+        if self.player.health <= Health::ZERO {
+            return;
+        }
+        let mut collected = Vec::new();
+        for (id, pickup) in query_pickup_ref!(self.level.pickups).iter() {
+            if self.player.inventory.len() + collected.len() >= INVENTORY_SIZE {
+                break;
+            }
+            if self.player.collider.check(pickup.collider) {
+                collected.push(id);
+            }
+        }
+
+        collected.sort();
+        for id in collected.into_iter().rev() {
+            let Some(pickup) = self.level.pickups.remove(id) else {
+                continue;
+            };
+            self.player.inventory.push(pickup.item);
+            self.assets.sounds.deliver.play();
+            self.particles.insert(Particle {
+                position: pickup.collider.pos(),
+                velocity: vec2(Coord::ZERO, Coord::new(0.5)),
+                lifetime: Time::new(1.0),
+                radius: Coord::new(0.3),
+                color: pickup.item.color(),
+                text: Some(pickup.item.name().to_owned()),
+            });
+        }
+    }
+
+    /// Uses the item in the inventory slot.
+    pub fn use_item(&mut self, slot: usize) {
+        if self.player.health <= Health::ZERO || slot >= self.player.inventory.len() {
+            return;
+        }
+        let item = self.player.inventory.remove(slot);
+        match item {
+            ItemKind::RepairKit => {
+                self.player.health =
+                    (self.player.health + Health::new(REPAIR_HEALTH)).min(Health::new(100.0));
+            }
+            ItemKind::Cloak => {
+                self.player.cloak = Time::new(CLOAK_DURATION);
+            }
+            ItemKind::Decoy => {
+                self.decoys.push(Decoy {
+                    position: self.player.collider.pos(),
+                    time: Time::new(DECOY_DURATION),
+                });
+            }
+        }
+    }
+
+    pub(super) fn update_items(&mut self, delta_time: Time) {
+        self.player.cloak = (self.player.cloak - delta_time).max(Time::ZERO);
+
+        for decoy in &mut self.decoys {
+            decoy.time -= delta_time;
+        }
+        self.decoys.retain(|decoy| decoy.time > Time::ZERO);
+
+        // The guards near a decoy keep looking at it
+        #[derive(StructQuery)]
+        struct ObstacleRef<'a> {
+            collider: &'a Collider,
+            lights: &'a Vec<Spotlight>,
+            path: &'a Option<Path>,
+            alert: &'a mut Option<Alert>,
+        }
+        let mut query = query_obstacle_ref!(self.obstacles);
+        let mut iter = query.iter_mut();
+        while let Some((_, item)) = iter.next() {
+            if item.path.is_none() || item.lights.is_empty() {
+                continue;
+            }
+            let decoy = self.decoys.iter().find(|decoy| {
+                (decoy.position - item.collider.pos()).len().as_f32() <= DECOY_RADIUS
+            });
+            if let Some(decoy) = decoy {
+                *item.alert = Some(Alert {
+                    position: decoy.position,
+                    time: decoy.time,
+                });
+            }
+        }
+
+        self.pickup_timer -= delta_time;
+        if self.pickup_timer <= Time::ZERO {
+            self.pickup_timer = Time::new(PICKUP_SPAWN_INTERVAL);
+            self.spawn_pickup();
+        }
+    }
+
+    /// Places a random pickup on a free spot around the player.
+    fn spawn_pickup(&mut self) {
+        if self.level.pickups.ids().count() >= PICKUPS_MAX {
+            return;
+        }
+        let mut rng = thread_rng();
+        let center = self.player.collider.pos();
+        let reachable = self.nav.reachable(center);
+        let position = (0..20).find_map(|_| {
+            let angle = Angle::new_radians(rng.gen_range(0.0..f32::PI * 2.0));
+            let distance = rng.gen_range(PICKUP_SPAWN_DISTANCE_MIN..PICKUP_SPAWN_DISTANCE_MAX);
+            let position = center + (angle.unit_direction() * distance).map(Coord::new);
+            reachable
+                .contains(&NavGrid::cell_at(position))
+                .then_some(position)
+        });
+        let Some(position) = position else {
+            return;
+        };
+        let item = *ItemKind::ALL.choose(&mut rng).unwrap();
+        self.level.pickups.insert(Pickup {
+            collider: Collider::new(
+                Aabb2::point(position).extend_uniform(Coord::new(PICKUP_SIZE / 2.0)),
+            ),
+            item,
+        });
+    }
+}
@@ -1,18 +1,60 @@
 use super::*;
 
 #[derive(Clone, Serialize, Deserialize)]
-#[serde(into = "LevelSerde", from = "LevelSerde")]
+#[serde(into = "LevelSerde", try_from = "serde_json::Value")]
 pub struct Level {
     pub spawn_point: vec2<Coord>,
     pub global_light: GlobalLight,
+    /// Overrides the global light over the run.
+    pub day_cycle: Option<DayCycle>,
+    pub weather: Vec<WeatherWindow>,
+    pub vehicle: VehicleProfile,
     pub waypoints: StructOf<Vec<Waypoint>>,
     pub obstacles: StructOf<Vec<Obstacle>>,
     pub lamps: StructOf<Vec<Lamp>>,
     pub props: StructOf<Vec<Prop>>,
+    pub hiding_zones: StructOf<Vec<HidingZone>>,
+    pub pickups: StructOf<Vec<Pickup>>,
 }
 
 pub type PropType = String;
 
+impl Level {
+    /// Bounding box of the spawn point and all entities in the level.
+    pub fn bounds(&self) -> Aabb2<Coord> {
+        let colliders = self
+            .waypoints
+            .collider
+            .iter()
+            .chain(self.obstacles.collider.iter())
+            .chain(self.lamps.collider.iter())
+            .chain(self.props.collider.iter())
+            .chain(self.hiding_zones.collider.iter())
+            .chain(self.pickups.collider.iter());
+        colliders.fold(Aabb2::point(self.spawn_point), |bounds, (_, collider)| {
+            aabb_union(bounds, collider.bounding_box())
+        })
+    }
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self {
+            spawn_point: vec2::ZERO,
+            global_light: default(),
+            day_cycle: None,
+            weather: Vec::new(),
+            vehicle: default(),
+            waypoints: StructOf::new(),
+            obstacles: StructOf::new(),
+            lamps: StructOf::new(),
+            props: StructOf::new(),
+            hiding_zones: StructOf::new(),
+            pickups: StructOf::new(),
+        }
+    }
+}
+
 #[derive(StructOf, Serialize, Deserialize, Default)]
 pub struct Prop {
     pub collider: Collider,
@@ -48,6 +90,18 @@ impl Default for Lamp {
     }
 }
 
+impl LampState {
+    /// How much the lamp is turned on, from 0 to 1.
+    pub fn brightness(&self, up_time: Time, down_time: Time) -> f32 {
+        let t = match *self {
+            LampState::Up(time) => (up_time - time).as_f32(),
+            LampState::Down(time) => 1.0 - (down_time - time).as_f32(),
+        };
+        let t = t.clamp(0.0, 1.0);
+        3.0 * t * t - 2.0 * t * t * t // Smoothstep
+    }
+}
+
 impl Default for LampState {
     fn default() -> Self {
         Self::Down(Time::ZERO)
@@ -62,6 +116,19 @@ pub struct Obstacle {
     /// In relative coordinates.
     pub lights: Vec<Spotlight>,
     pub path: Option<Path>,
+    #[serde(skip)]
+    pub alert: Option<Alert>,
+    /// Chunk of the endless mode that the obstacle belongs to.
+    #[serde(skip)]
+    pub chunk: Option<vec2<i32>>,
+}
+
+/// The obstacle heard a noise and looks towards it.
+#[derive(Debug, Clone, Copy)]
+pub struct Alert {
+    pub position: vec2<Coord>,
+    /// Time left until the obstacle returns to its path.
+    pub time: Time,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -90,12 +157,35 @@ pub struct Waypoint {
     pub collider: Collider,
 }
 
+/// Area where the player stays unseen while driving slowly.
+#[derive(StructOf, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct HidingZone {
+    pub collider: Collider,
+}
+
+/// An item lying in the level that the player can collect.
+#[derive(StructOf, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Pickup {
+    pub collider: Collider,
+    pub item: ItemKind,
+}
+
 #[derive(Serialize, Deserialize)]
 struct LevelSerde {
+    #[serde(default)]
+    pub version: u32,
     pub spawn_point: vec2<Coord>,
     #[serde(default)]
     pub global_light: GlobalLight,
     #[serde(default)]
+    pub day_cycle: Option<DayCycle>,
+    #[serde(default)]
+    pub weather: Vec<WeatherWindow>,
+    #[serde(default)]
+    pub vehicle: VehicleProfile,
+    #[serde(default)]
     pub waypoints: Vec<Waypoint>,
     #[serde(default)]
     pub obstacles: Vec<Obstacle>,
@@ -103,13 +193,21 @@ struct LevelSerde {
     pub lamps: Vec<Lamp>,
     #[serde(default)]
     pub props: Vec<Prop>,
+    #[serde(default)]
+    pub hiding_zones: Vec<HidingZone>,
+    #[serde(default)]
+    pub pickups: Vec<Pickup>,
 }
 
 impl From<Level> for LevelSerde {
     fn from(level: Level) -> Self {
         Self {
+            version: LEVEL_VERSION,
             spawn_point: level.spawn_point,
             global_light: level.global_light,
+            day_cycle: level.day_cycle,
+            weather: level.weather,
+            vehicle: level.vehicle,
             waypoints: level
                 .waypoints
                 .inner
@@ -134,10 +232,32 @@ impl From<Level> for LevelSerde {
                 .into_iter()
                 .map(|(_, item)| item)
                 .collect(),
+            hiding_zones: level
+                .hiding_zones
+                .inner
+                .into_iter()
+                .map(|(_, item)| item)
+                .collect(),
+            pickups: level
+                .pickups
+                .inner
+                .into_iter()
+                .map(|(_, item)| item)
+                .collect(),
         }
     }
 }
 
+impl TryFrom<serde_json::Value> for Level {
+    type Error = anyhow::Error;
+
+    fn try_from(mut document: serde_json::Value) -> Result<Self, Self::Error> {
+        migrate_level(&mut document)?;
+        let level: LevelSerde = serde_json::from_value(document)?;
+        Ok(level.into())
+    }
+}
+
 impl From<LevelSerde> for Level {
     fn from(level: LevelSerde) -> Self {
         let mut waypoints = StructOf::<Vec<Waypoint>>::new();
@@ -160,13 +280,28 @@ impl From<LevelSerde> for Level {
             props.insert(item);
         }
 
+        let mut hiding_zones = StructOf::<Vec<HidingZone>>::new();
+        for item in level.hiding_zones {
+            hiding_zones.insert(item);
+        }
+
+        let mut pickups = StructOf::<Vec<Pickup>>::new();
+        for item in level.pickups {
+            pickups.insert(item);
+        }
+
         Self {
             spawn_point: level.spawn_point,
             global_light: level.global_light,
+            day_cycle: level.day_cycle,
+            weather: level.weather,
+            vehicle: level.vehicle,
             waypoints,
             obstacles,
             lamps,
             props,
+            hiding_zones,
+            pickups,
         }
     }
 }
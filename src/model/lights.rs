@@ -23,6 +23,25 @@ pub struct Spotlight {
     pub volume: f32,
 }
 
+/// Animates the global light over a run, from dusk through midnight to dawn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DayCycle {
+    /// Length of the night in seconds.
+    pub duration: Time,
+    /// Global light intensity below which the lamps are turned on.
+    pub lamp_threshold: f32,
+    /// Sorted by time.
+    pub keyframes: Vec<LightKeyframe>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LightKeyframe {
+    /// Fraction of the cycle, from 0 to 1.
+    pub time: f32,
+    pub light: GlobalLight,
+}
+
 fn default_gradient() -> f32 {
     1.0
 }
@@ -36,6 +55,51 @@ impl Default for GlobalLight {
     }
 }
 
+impl DayCycle {
+    /// Fraction of the cycle passed at the time since the start of the run.
+    pub fn progress(&self, time: Time) -> f32 {
+        if self.duration <= Time::ZERO {
+            return 1.0;
+        }
+        (time / self.duration).as_f32().clamp(0.0, 1.0)
+    }
+
+    /// Global light interpolated between the keyframes.
+    pub fn light_at(&self, time: Time) -> Option<GlobalLight> {
+        let t = self.progress(time);
+        let next = self.keyframes.iter().position(|frame| frame.time > t);
+        let frame = match next {
+            Some(0) => return self.keyframes.first().map(|frame| frame.light),
+            Some(i) => i,
+            None => return self.keyframes.last().map(|frame| frame.light),
+        };
+        let (from, to) = (self.keyframes[frame - 1], self.keyframes[frame]);
+        let t = (t - from.time) / (to.time - from.time);
+        Some(GlobalLight {
+            color: Rgba::lerp(from.light.color, to.light.color, t),
+            intensity: from.light.intensity + (to.light.intensity - from.light.intensity) * t,
+        })
+    }
+}
+
+impl Default for DayCycle {
+    fn default() -> Self {
+        let keyframe = |time, color, intensity| LightKeyframe {
+            time,
+            light: GlobalLight { color, intensity },
+        };
+        Self {
+            duration: Time::new(300.0),
+            lamp_threshold: 0.4,
+            keyframes: vec![
+                keyframe(0.0, Rgba::opaque(1.0, 0.6, 0.4), 0.6),
+                keyframe(0.5, Rgba::opaque(0.4, 0.5, 1.0), 0.1),
+                keyframe(1.0, Rgba::opaque(1.0, 0.7, 0.8), 0.5),
+            ],
+        }
+    }
+}
+
 impl Default for Spotlight {
     fn default() -> Self {
         Self {
@@ -3,20 +3,70 @@ use super::*;
 const CAMERA_INTERPOLATION: f32 = 0.5;
 
 const OBSTACLE_SPAWN_DISTANCE_MIN: f32 = 15.0;
-const WAYPOINT_DISTANCE_MIN: f32 = 5.0;
-const WAYPOINT_DISTANCE_MAX: f32 = 20.0;
+pub(super) const WAYPOINT_DISTANCE_MIN: f32 = 5.0;
+pub(super) const WAYPOINT_DISTANCE_MAX: f32 = 20.0;
 
 // const DEATH_PENALTY: Score = 1000;
 const DELIVER_SCORE: Score = 500;
 const SHADOW_BONUS: Score = 1000;
 const SHADOW_MAX_VIS: f32 = 0.05;
-
-// const HEALTH_RESTORE: f32 = 10.0;
+/// Multiplier gained for each delivery in the streak.
+const MULTIPLIER_STEP: f32 = 0.5;
+const MULTIPLIER_MAX: f32 = 5.0;
+/// Multiplier lost per second of full visibility, the streak ends when it is back to 1.
+const MULTIPLIER_DECAY: f32 = 2.0;
+/// Visibility above which the light counts as touching the player.
+const NEAR_MISS_MIN_VIS: f32 = 0.005;
+const NEAR_MISS_SCORE: Score = 50;
+const NEAR_MISS_COOLDOWN: f32 = 2.0;
+
+/// Health restored per second while hiding.
+const HEALTH_RESTORE: f32 = 10.0;
+/// The player stays hidden only while slower than this.
+const HIDING_SPEED_MAX: f32 = 1.0;
 const PLAYER_DRAG: f32 = 0.2;
 const PLAYER_MAX_SPEED: f32 = 5.0;
 const PLAYER_TURN_SPEED: f32 = 3.0;
 const PLAYER_ACCELERATION: f32 = 10.0;
 
+const BOOST_MAX_SPEED: f32 = 8.0;
+const BOOST_ACCELERATION: f32 = 20.0;
+/// Stamina needed to start boosting.
+const BOOST_MIN_STAMINA: f32 = 0.2;
+/// Stamina spent per second of boosting.
+const STAMINA_DRAIN: f32 = 0.5;
+/// Stamina restored per second in the shadow.
+const STAMINA_RECHARGE: f32 = 0.2;
+const BRAKE_DECELERATION: f32 = 6.0;
+/// Turn speed multiplier while drifting.
+const DRIFT_TURN: f32 = 1.8;
+/// Grip multiplier while drifting.
+const DRIFT_GRIP: f32 = 0.25;
+
+/// Added to the score when spawning the obstacles in full darkness,
+/// so that the traffic grows towards midnight.
+const NIGHT_DIFFICULTY_BONUS: Score = 2000;
+/// Range of the global light intensity over which the lamps fade in.
+const LAMP_SWITCH_RANGE: f32 = 0.1;
+
+/// Noise made at the full speed.
+const SPEED_NOISE: f32 = 0.5;
+const BRAKE_NOISE: f32 = 0.3;
+const BOUNCE_NOISE: f32 = 1.0;
+/// How fast the noise fades per second.
+const NOISE_DECAY: f32 = 0.5;
+/// Distance at which the loudest noise is heard.
+pub const NOISE_RADIUS_MAX: f32 = 15.0;
+/// How long the obstacles look towards the noise.
+const ALERT_DURATION: f32 = 3.0;
+/// The obstacles are alerted only while the noise grows faster than this per second,
+/// so that a steady engine hum does not keep them staring.
+const NOISE_ALERT_RISE: f32 = 0.1;
+
+const ROUTE_UPDATE_INTERVAL: f32 = 0.5;
+/// Extra cost of driving through a fully lit cell when preferring dark routes.
+const DARK_ROUTE_WEIGHT: f32 = 20.0;
+
 impl World {
     pub fn update(
         &mut self,
@@ -25,27 +75,89 @@ impl World {
         delta_time: Time,
     ) {
         self.time += delta_time;
+        self.update_weather(delta_time);
+        let mut player_visibility = player_visibility * r32(self.weather.visibility);
 
         self.update_difficulty(delta_time);
         self.update_particles(delta_time);
-        self.update_player(player_visibility, delta_time);
+        self.update_items(delta_time);
+        self.player.hidden = self.is_player_hiding();
+        let concealed = self.player.hidden || self.player.cloak > Time::ZERO;
+        if concealed {
+            player_visibility = R32::ZERO;
+        }
+        // The headlight gives the player away without hurting them
+        let detection = if self.player.headlight && !concealed {
+            (player_visibility + r32(self.player.vehicle.headlight_visibility)).min(R32::ONE)
+        } else {
+            player_visibility
+        };
+        self.update_player(player_visibility, detection, delta_time);
+        let braking = (player_control.brake || player_control.accelerate < Coord::ZERO)
+            && vec2::dot(
+                self.player.velocity,
+                self.player
+                    .collider
+                    .rotation
+                    .unit_direction()
+                    .map(Coord::new),
+            ) > Coord::ZERO;
         self.control_player(player_control, delta_time);
         self.obstacles_movement(delta_time);
         self.player_movement(delta_time);
         self.collisions();
+        self.update_noise(braking, delta_time);
+        self.update_headlight();
         self.waypoints();
+        self.pickups();
+        self.update_route(delta_time);
         self.update_lamps(delta_time);
         self.update_camera(delta_time);
     }
 
+    /// Current global light, animated by the day cycle if the level has one.
+    pub fn global_light(&self) -> GlobalLight {
+        self.level
+            .day_cycle
+            .as_ref()
+            .and_then(|cycle| cycle.light_at(self.time))
+            .unwrap_or(self.level.global_light)
+    }
+
+    /// How much the lamps are turned on by the day cycle, from 0 to 1.
+    pub fn lamps_power(&self) -> f32 {
+        let Some(cycle) = &self.level.day_cycle else {
+            return 1.0;
+        };
+        let intensity = self.global_light().intensity;
+        ((cycle.lamp_threshold - intensity) / LAMP_SWITCH_RANGE + 0.5).clamp(0.0, 1.0)
+    }
+
+    /// Score the difficulty of the obstacles is compared against.
+    fn difficulty_score(&self) -> Score {
+        let Some(cycle) = &self.level.day_cycle else {
+            return self.player.score;
+        };
+        let brightest = cycle
+            .keyframes
+            .iter()
+            .map(|frame| frame.light.intensity)
+            .fold(0.0, f32::max);
+        if brightest <= 0.0 {
+            return self.player.score;
+        }
+        let darkness = (1.0 - self.global_light().intensity / brightest).clamp(0.0, 1.0);
+        self.player.score + (NIGHT_DIFFICULTY_BONUS as f32 * darkness) as Score
+    }
+
     fn update_difficulty(&mut self, _delta_time: Time) {
+        let score = self.difficulty_score();
         let mut new = Vec::new();
         for (id, obstacle) in self.level.obstacles.iter() {
             let delta = obstacle.collider.pos() - self.player.collider.pos();
             let distance = delta.len();
             if *obstacle.difficulty == 0
-                || *obstacle.difficulty <= self.player.score
-                    && distance.as_f32() > OBSTACLE_SPAWN_DISTANCE_MIN
+                || *obstacle.difficulty <= score && distance.as_f32() > OBSTACLE_SPAWN_DISTANCE_MIN
             {
                 new.push(id);
             }
@@ -108,20 +220,44 @@ impl World {
         }
     }
 
-    fn update_player(&mut self, visibility: R32, delta_time: Time) {
+    /// `visibility` is the light hurting the player,
+    /// and `detection` is how visible they are to the others.
+    fn update_player(&mut self, visibility: R32, detection: R32, delta_time: Time) {
         if self.player.health <= Health::ZERO {
             return;
         }
 
-        // if visibility == R32::ZERO {
-        //     self.player.health = (self.player.health + Health::new(HEALTH_RESTORE) * delta_time)
-        //         .min(Health::new(100.0));
-        // }
+        if self.player.hidden {
+            self.player.health = (self.player.health + Health::new(HEALTH_RESTORE) * delta_time)
+                .min(Health::new(100.0));
+        }
 
-        if visibility.as_f32() < SHADOW_MAX_VIS {
+        self.player.near_miss_cooldown -= delta_time;
+        if detection.as_f32() < SHADOW_MAX_VIS {
+            self.player.stamina =
+                (self.player.stamina + STAMINA_RECHARGE * delta_time.as_f32()).min(1.0);
+
+            // The light touched the player without giving them away
+            let grazing = detection.as_f32() >= NEAR_MISS_MIN_VIS;
+            if grazing && !self.player.grazing && self.player.near_miss_cooldown <= Time::ZERO {
+                self.player.near_miss_cooldown = Time::new(NEAR_MISS_COOLDOWN);
+                let score = self.multiplied(NEAR_MISS_SCORE);
+                self.player.score += score;
+                self.score_particle(format!("Near miss +{score}"), Rgba::new(0.9, 0.9, 0.3, 0.8));
+            }
+            self.player.grazing = grazing;
             return;
         }
+        self.player.grazing = false;
         self.player.shadow_bonus = false;
+        self.player.multiplier = (self.player.multiplier
+            - MULTIPLIER_DECAY * detection.as_f32() * delta_time.as_f32())
+        .max(1.0);
+        // The streak survives a glimpse, and ends once the multiplier is gone
+        if self.player.multiplier <= 1.0 && self.player.streak > 0 {
+            self.player.streak = 0;
+            self.score_particle("Streak lost".to_owned(), Rgba::new(1.0, 0.3, 0.3, 0.8));
+        }
 
         // Particles
         let p = f64::from(visibility.as_f32()) * 0.5;
@@ -156,6 +292,70 @@ impl World {
         }
     }
 
+    fn is_player_hiding(&self) -> bool {
+        if self.player.health <= Health::ZERO
+            || self.player.velocity.len().as_f32() > HIDING_SPEED_MAX
+        {
+            return false;
+        }
+        let position = self.player.collider.pos();
+        self.level.hiding_zones.collider.iter().any(|(_, zone)| {
+            zone.check(&Collider::new(
+                Aabb2::point(position).extend_uniform(Coord::new(0.01)),
+            ))
+        })
+    }
+
+    /// Distance at which the player's noise is heard.
+    pub fn noise_radius(&self) -> Coord {
+        Coord::new(self.player.noise * NOISE_RADIUS_MAX)
+    }
+
+    fn update_noise(&mut self, braking: bool, delta_time: Time) {
+        let player = &mut self.player;
+        let mut target = if player.health > Health::ZERO {
+            player.velocity.len().as_f32() / PLAYER_MAX_SPEED * SPEED_NOISE
+        } else {
+            0.0
+        };
+        if braking {
+            target += BRAKE_NOISE;
+        }
+        if self.bounced {
+            target = target.max(BOUNCE_NOISE);
+        }
+        let decayed = player.noise - NOISE_DECAY * delta_time.as_f32();
+        let previous = player.noise;
+        player.noise = target.max(decayed).clamp(0.0, 1.0);
+        if player.noise - previous <= NOISE_ALERT_RISE * delta_time.as_f32() {
+            return;
+        }
+
+        // Obstacles with lights look towards the new noise
+        #[derive(StructQuery)]
+        struct ObstacleRef<'a> {
+            collider: &'a Collider,
+            lights: &'a Vec<Spotlight>,
+            path: &'a Option<Path>,
+            alert: &'a mut Option<Alert>,
+        }
+        let position = self.player.collider.pos();
+        let radius = self.noise_radius();
+        let mut query = query_obstacle_ref!(self.obstacles);
+        let mut iter = query.iter_mut();
+        while let Some((_, item)) = iter.next() {
+            if item.path.is_none() || item.lights.is_empty() {
+                continue;
+            }
+            if (item.collider.pos() - position).len() <= radius {
+                *item.alert = Some(Alert {
+                    position,
+                    time: Time::new(ALERT_DURATION),
+                });
+            }
+        }
+    }
+
     fn kill_player(&mut self) {
         self.assets.sounds.death.play();
         self.death_time = Some(self.time);
@@ -172,14 +372,35 @@ impl World {
             return;
         }
 
+        self.update_boost(control.boost, delta_time);
+
+        let turn_speed = if control.brake {
+            PLAYER_TURN_SPEED * DRIFT_TURN
+        } else {
+            PLAYER_TURN_SPEED
+        };
         self.player.collider.rotation +=
-            Angle::new_radians(control.turn.as_f32() * PLAYER_TURN_SPEED * delta_time.as_f32());
+            Angle::new_radians(control.turn.as_f32() * turn_speed * delta_time.as_f32());
 
+        let (max_speed, acceleration) = if self.player.boosting {
+            (BOOST_MAX_SPEED, BOOST_ACCELERATION)
+        } else {
+            (PLAYER_MAX_SPEED, PLAYER_ACCELERATION)
+        };
         let mut speed = self.player.velocity.len();
         speed -= speed * Coord::new(1.0 - PLAYER_DRAG) * delta_time;
-        let target_speed =
-            speed + control.accelerate * Coord::new(PLAYER_ACCELERATION) * delta_time;
-        speed = target_speed.clamp(Coord::ZERO, Coord::new(PLAYER_MAX_SPEED));
+        if control.brake {
+            speed -= Coord::new(BRAKE_DECELERATION) * delta_time;
+        }
+        let accelerate = if self.player.boosting {
+            Coord::ONE
+        } else {
+            control.accelerate
+        };
+        let target_speed = speed + accelerate * Coord::new(acceleration) * delta_time;
+        // Slow down gradually after the boost
+        let max_speed = Coord::new(max_speed).max(speed);
+        speed = target_speed.clamp(Coord::ZERO, max_speed);
 
         let target_velocity = self
             .player
@@ -188,20 +409,75 @@ impl World {
             .unit_direction()
             .map(Coord::new)
             * speed;
+        let grip = if control.brake {
+            self.weather.grip * DRIFT_GRIP
+        } else {
+            self.weather.grip
+        };
         self.player.velocity += (target_velocity - self.player.velocity)
-            .clamp_len(..=Coord::new(PLAYER_ACCELERATION) * delta_time);
+            .clamp_len(..=Coord::new(acceleration * grip) * delta_time);
     }
 
-    fn obstacles_movement(&mut self, delta_time: Time) {
+    fn update_boost(&mut self, boost: bool, delta_time: Time) {
+        let player = &mut self.player;
+        let was_boosting = player.boosting;
+        player.boosting =
+            boost && player.stamina > 0.0 && (was_boosting || player.stamina >= BOOST_MIN_STAMINA);
+        if !player.boosting {
+            return;
+        }
+        player.stamina = (player.stamina - STAMINA_DRAIN * delta_time.as_f32()).max(0.0);
+        if !was_boosting {
+            self.assets.sounds.boost.play();
+        }
+
+        // Exhaust flames
+        let mut rng = thread_rng();
+        if rng.gen_bool(0.5) {
+            let direction = player.collider.rotation.unit_direction().map(Coord::new);
+            let back =
+                player.collider.pos() - direction * player.collider.size().x / Coord::new(2.0);
+            let angle = Coord::new(rng.gen_range(-0.5..0.5));
+            self.particles.insert(Particle {
+                position: rng.gen_circle(back, Coord::new(0.05)),
+                velocity: -direction.rotate(angle) * Coord::new(2.0),
+                lifetime: Time::new(0.3),
+                radius: Coord::new(0.1),
+                color: Rgba::opaque(1.0, 0.6, 0.1),
+                text: None,
+            });
+        }
+    }
+
+    pub fn obstacles_movement(&mut self, delta_time: Time) {
         #[derive(StructQuery)]
         struct ObstacleRef<'a> {
             collider: &'a mut Collider,
             #[query(component = "Option<Path>")]
             path: &'a mut Path,
+            alert: &'a mut Option<Alert>,
         }
         let mut query = query_obstacle_ref!(self.obstacles);
         let mut iter = query.iter_mut();
         while let Some((_, item)) = iter.next() {
+            // Stop and look towards the noise
+            if let Some(alert) = item.alert {
+                alert.time -= delta_time;
+                let delta = alert.position - item.collider.pos();
+                let target_angle = Angle::new_radians(delta.arg().as_f32());
+                let max_delta = Angle::new_radians(
+                    (item.path.angular_speed * delta_time)
+                        .as_f32()
+                        .clamp_abs(f32::PI),
+                );
+                item.collider.rotation +=
+                    (target_angle - item.collider.rotation).clamp_abs(max_delta);
+                if alert.time <= Time::ZERO {
+                    *item.alert = None;
+                }
+                continue;
+            }
+
             let Some(&target) = item.path.points.get(item.path.next_point) else {
                 item.path.next_point = 0;
                 continue;
@@ -302,30 +578,57 @@ impl World {
         };
 
         if player.collider.check(active.collider) {
-            let mut score = DELIVER_SCORE;
-            if player.shadow_bonus {
-                score += SHADOW_BONUS;
+            let position = active.collider.pos();
+            let shadow = player.shadow_bonus;
+            if shadow {
+                player.streak += 1;
+                player.multiplier = (player.multiplier + MULTIPLIER_STEP).min(MULTIPLIER_MAX);
             }
-            player.score += score;
             player.shadow_bonus = true;
 
+            let score = self.multiplied(DELIVER_SCORE);
+            self.player.score += score;
             self.assets.sounds.deliver.play();
-            let angle = Angle::new_radians(thread_rng().gen_range(1.47..1.77));
-            let velocity = (angle.unit_direction() * 0.5).map(Coord::new);
-            self.particles.insert(Particle {
-                position: active.collider.pos(),
-                velocity,
-                lifetime: Time::new(1.0),
-                radius: Coord::new(0.5),
-                color: Rgba::new(0.0, 0.8, 0.7, 0.7),
-                text: Some(format!("+{score}")),
-            });
+            self.text_particle(position, format!("+{score}"), Rgba::new(0.0, 0.8, 0.7, 0.7));
+            if shadow {
+                let bonus = self.multiplied(SHADOW_BONUS);
+                self.player.score += bonus;
+                self.score_particle(
+                    format!("Shadow x{:.1} +{bonus}", self.player.multiplier),
+                    Rgba::new(0.5, 0.4, 1.0, 0.8),
+                );
+            }
 
             self.next_waypoint();
         }
     }
 
-    fn next_waypoint(&mut self) {
+    /// Applies the streak multiplier to the score.
+    fn multiplied(&self, score: Score) -> Score {
+        (score as f32 * self.player.multiplier).round() as Score
+    }
+
+    /// Shows a floating text rising from the position.
+    fn text_particle(&mut self, position: vec2<Coord>, text: String, color: Color) {
+        let angle = Angle::new_radians(thread_rng().gen_range(1.47..1.77));
+        let velocity = (angle.unit_direction() * 0.5).map(Coord::new);
+        self.particles.insert(Particle {
+            position,
+            velocity,
+            lifetime: Time::new(1.0),
+            radius: Coord::new(0.5),
+            color,
+            text: Some(text),
+        });
+    }
+
+    /// Shows a floating text above the player.
+    fn score_particle(&mut self, text: String, color: Color) {
+        let position = self.player.collider.pos() + vec2(Coord::ZERO, Coord::ONE);
+        self.text_particle(position, text, color);
+    }
+
+    pub(super) fn next_waypoint(&mut self) {
         #[derive(StructQuery)]
         struct WaypointRef<'a> {
             collider: &'a Collider,
@@ -339,7 +642,7 @@ impl World {
 
         let mut rng = thread_rng();
 
-        let next = self
+        let candidates: Vec<_> = self
             .level
             .waypoints
             .ids()
@@ -350,11 +653,46 @@ impl World {
                 let distance = delta.len().as_f32();
                 (WAYPOINT_DISTANCE_MIN..=WAYPOINT_DISTANCE_MAX).contains(&distance)
             })
-            .map(|(id, _)| id)
+            .collect();
+
+        // In the endless mode the deliveries lead into the unexplored chunks
+        let fresh = candidates
+            .iter()
+            .filter(|(_, item)| {
+                self.endless
+                    .as_ref()
+                    .map_or(false, |endless| !endless.is_visited(item.collider.pos()))
+            })
+            .map(|&(id, _)| id)
             .choose(&mut rng);
 
-        let next = next.or_else(|| self.level.waypoints.ids().choose(&mut rng));
+        let next = fresh
+            .or_else(|| candidates.iter().map(|&(id, _)| id).choose(&mut rng))
+            .or_else(|| self.level.waypoints.ids().choose(&mut rng));
         self.active_waypoint = next.unwrap_or(0);
+        self.route_timer = Time::ZERO;
+    }
+
+    fn update_route(&mut self, delta_time: Time) {
+        self.route_timer -= delta_time;
+        if self.route_timer > Time::ZERO {
+            return;
+        }
+        self.route_timer = Time::new(ROUTE_UPDATE_INTERVAL);
+
+        let Some(target) = self.level.waypoints.collider.get(self.active_waypoint) else {
+            self.route.clear();
+            return;
+        };
+        let dark_weight = if self.prefer_dark_routes {
+            DARK_ROUTE_WEIGHT
+        } else {
+            0.0
+        };
+        self.route = self
+            .nav
+            .find_path(self.player.collider.pos(), target.pos(), dark_weight)
+            .unwrap_or_default();
     }
 
     fn update_camera(&mut self, delta_time: Time) {
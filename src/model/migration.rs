@@ -0,0 +1,54 @@
+use super::*;
+
+use serde_json::Value;
+
+/// Version of the level format written by this build.
+pub const LEVEL_VERSION: u32 = 1;
+
+/// Upgrades a level document by a single version.
+type Migration = fn(&mut Value) -> anyhow::Result<()>;
+
+/// Migrations in order, the one at index `i` upgrades version `i` to `i + 1`.
+const MIGRATIONS: [Migration; LEVEL_VERSION as usize] = [migrate_v0];
+
+/// Returns the version of the level document.
+/// Levels saved before the versioning was introduced are version 0.
+pub fn level_version(document: &Value) -> anyhow::Result<u32> {
+    match document.get("version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| anyhow::anyhow!("invalid level version: {version}")),
+    }
+}
+
+/// Upgrades the level document to the current version step by step.
+/// Documents from a newer version are left as is.
+pub fn migrate_level(document: &mut Value) -> anyhow::Result<()> {
+    let version = level_version(document)?;
+    if version > LEVEL_VERSION {
+        log::warn!(
+            "The level was saved by a newer version of the game \
+            (format {version}, supported {LEVEL_VERSION}), some data might be lost"
+        );
+        return Ok(());
+    }
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(document).map_err(|err| {
+            err.context(format!("failed to migrate the level from version {from}"))
+        })?;
+        if let Value::Object(object) = document {
+            object.insert("version".to_owned(), Value::from(from + 1));
+        }
+    }
+    Ok(())
+}
+
+/// The first versioned format is the same as the unversioned one.
+fn migrate_v0(document: &mut Value) -> anyhow::Result<()> {
+    if !document.is_object() {
+        anyhow::bail!("expected the level to be an object");
+    }
+    Ok(())
+}
@@ -2,16 +2,35 @@ use super::*;
 
 mod angle;
 mod collider;
+mod endless;
+mod generator;
 mod geometry;
+mod items;
 mod level;
 mod lights;
 mod logic;
+mod migration;
+mod navigation;
+mod roads;
+mod validation;
+mod vehicle;
+mod weather;
 mod world;
 
 pub use angle::*;
 pub use collider::*;
+pub use endless::*;
+pub use generator::*;
+pub use geometry::*;
+pub use items::*;
 pub use level::*;
 pub use lights::*;
+pub use migration::*;
+pub use navigation::*;
+pub use roads::*;
+pub use validation::*;
+pub use vehicle::*;
+pub use weather::*;
 pub use world::*;
 
 const PLAYER_SIZE: vec2<f32> = vec2(0.6, 0.2);
@@ -28,11 +47,35 @@ pub struct Player {
     pub health: Health,
     pub collider: Collider,
     pub velocity: vec2<Coord>,
+    /// How much noise the player makes, from 0 to 1.
+    pub noise: f32,
+    /// Fuel for the boost, from 0 to 1.
+    pub stamina: f32,
+    pub boosting: bool,
+    pub vehicle: VehicleProfile,
+    pub headlight: bool,
+    /// Whether the player is hiding in a hiding zone.
+    pub hidden: bool,
+    pub inventory: Vec<ItemKind>,
+    /// Time left until the shadow cloak wears off.
+    pub cloak: Time,
+    /// Multiplier of the score, grown by the shadow deliveries.
+    pub multiplier: f32,
+    /// Number of shadow deliveries since the multiplier was last back to 1.
+    /// A delivery after being seen keeps the streak without extending it.
+    pub streak: u32,
+    /// Whether the player is touched by the light without being seen.
+    pub grazing: bool,
+    /// Time left until the next near-miss can be rewarded.
+    pub near_miss_cooldown: Time,
 }
 
 pub struct PlayerControl {
     pub accelerate: Coord,
     pub turn: Coord,
+    pub boost: bool,
+    /// Handbrake, which lets the car drift in the turns.
+    pub brake: bool,
 }
 
 #[derive(StructOf)]
@@ -0,0 +1,229 @@
+use super::*;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// Size of a navigation grid cell in world units.
+pub const NAV_CELL_SIZE: f32 = 0.5;
+/// Free space the player needs on each side to drive past an obstacle.
+const NAV_CLEARANCE: f32 = PLAYER_SIZE.y / 2.0;
+/// Free cells around the level, so that the player can drive around its edges.
+const NAV_MARGIN: i32 = 4;
+
+/// Coarse grid of the cells the player can drive through,
+/// built from the static obstacles of the level.
+pub struct NavGrid {
+    /// Bounds of the grid in cells, inclusive.
+    bounds: Aabb2<i32>,
+    blocked: HashSet<vec2<i32>>,
+    /// Coverage of the cells by the static lamps.
+    light: HashMap<vec2<i32>, f32>,
+}
+
+impl NavGrid {
+    /// Builds the grid over the level from the obstacles in the given collections,
+    /// since the game moves the spawned obstacles out of the level.
+    pub fn new(level: &Level, obstacles: &[&StructOf<Vec<Obstacle>>]) -> Self {
+        let level_bounds = level.bounds();
+        let bounds = Aabb2 {
+            min: Self::cell_at(level_bounds.min) - vec2::splat(NAV_MARGIN),
+            max: Self::cell_at(level_bounds.max) + vec2::splat(NAV_MARGIN),
+        };
+
+        let mut blocked = HashSet::new();
+        for obstacles in obstacles {
+            for (id, collider) in obstacles.collider.iter() {
+                // Moving obstacles do not block the way permanently
+                if obstacles.path.get(id).map_or(false, Option::is_some) {
+                    continue;
+                }
+                let area = collider.bounding_box();
+                let min = Self::cell_at(area.min - vec2::splat(Coord::new(NAV_CLEARANCE)));
+                let max = Self::cell_at(area.max + vec2::splat(Coord::new(NAV_CLEARANCE)));
+                for x in min.x..=max.x {
+                    for y in min.y..=max.y {
+                        let cell = vec2(x, y);
+                        let aabb =
+                            Self::cell_bounds(cell).extend_uniform(Coord::new(NAV_CLEARANCE));
+                        if collider.check(&Collider::new(aabb)) {
+                            blocked.insert(cell);
+                        }
+                    }
+                }
+            }
+        }
+
+        #[derive(StructQuery)]
+        struct LampRef<'a> {
+            collider: &'a Collider,
+            light: &'a Spotlight,
+        }
+
+        // Shadows are ignored, so the coverage is a rough estimate
+        let mut light = HashMap::<vec2<i32>, f32>::new();
+        for item in query_lamp_ref!(level.lamps).values() {
+            let position = item.collider.pos() + item.light.position;
+            let radius = item.light.max_distance;
+            let min = Self::cell_at(position - vec2::splat(radius));
+            let max = Self::cell_at(position + vec2::splat(radius));
+            for x in min.x..=max.x {
+                for y in min.y..=max.y {
+                    let cell = vec2(x, y);
+                    let distance = (Self::cell_center(cell) - position).len();
+                    if distance < radius {
+                        let t = 1.0 - (distance / radius).as_f32();
+                        *light.entry(cell).or_default() += item.light.intensity * t;
+                    }
+                }
+            }
+        }
+
+        Self {
+            bounds,
+            blocked,
+            light,
+        }
+    }
+
+    pub fn cell_at(position: vec2<Coord>) -> vec2<i32> {
+        position.map(|x| (x.as_f32() / NAV_CELL_SIZE).floor() as i32)
+    }
+
+    pub fn cell_bounds(cell: vec2<i32>) -> Aabb2<Coord> {
+        let min = cell.map(|x| x as f32 * NAV_CELL_SIZE);
+        Aabb2::point(min)
+            .extend_positive(vec2::splat(NAV_CELL_SIZE))
+            .map(Coord::new)
+    }
+
+    pub fn cell_center(cell: vec2<i32>) -> vec2<Coord> {
+        Self::cell_bounds(cell).center()
+    }
+
+    /// Static light coverage of the cell.
+    pub fn light_at(&self, cell: vec2<i32>) -> f32 {
+        self.light.get(&cell).copied().unwrap_or(0.0)
+    }
+
+    /// Whether the cell is inside the grid.
+    fn contains(&self, cell: vec2<i32>) -> bool {
+        (self.bounds.min.x..=self.bounds.max.x).contains(&cell.x)
+            && (self.bounds.min.y..=self.bounds.max.y).contains(&cell.y)
+    }
+
+    /// Whether the player can drive through the cell.
+    pub fn is_free(&self, cell: vec2<i32>) -> bool {
+        self.contains(cell) && !self.blocked.contains(&cell)
+    }
+
+    /// Finds the free cell closest to the position,
+    /// since it might be inside the clearance around an obstacle.
+    pub fn nearest_free(&self, position: vec2<Coord>) -> Option<vec2<i32>> {
+        let start = Self::cell_at(position);
+        let mut visited = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        while let Some(cell) = queue.pop_front() {
+            if self.is_free(cell) {
+                return Some(cell);
+            }
+            for delta in [vec2(0, 1), vec2(1, 0), vec2(0, -1), vec2(-1, 0)] {
+                let next = cell + delta;
+                if self.contains(next) && visited.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// Free cells sharing a side with the cell.
+    pub fn neighbours(&self, cell: vec2<i32>) -> impl Iterator<Item = vec2<i32>> + '_ {
+        [vec2(0, 1), vec2(1, 0), vec2(0, -1), vec2(-1, 0)]
+            .into_iter()
+            .map(move |delta| cell + delta)
+            .filter(|&cell| self.is_free(cell))
+    }
+
+    /// Returns all cells reachable from the nearest free cell to the position.
+    pub fn reachable(&self, from: vec2<Coord>) -> HashSet<vec2<i32>> {
+        let mut visited = HashSet::new();
+        let Some(start) = self.nearest_free(from) else {
+            return visited;
+        };
+        visited.insert(start);
+        let mut queue = VecDeque::from([start]);
+        while let Some(cell) = queue.pop_front() {
+            for next in self.neighbours(cell) {
+                if visited.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        visited
+    }
+
+    /// Finds the cheapest route between the positions using A*.
+    /// Each lit cell costs `1 + dark_weight * light` to drive through,
+    /// so a positive `dark_weight` makes the route avoid the lamps.
+    /// Returns the corners of the route from `from` to `to`.
+    pub fn find_path(
+        &self,
+        from: vec2<Coord>,
+        to: vec2<Coord>,
+        dark_weight: f32,
+    ) -> Option<Vec<vec2<Coord>>> {
+        let start = Self::cell_at(from);
+        let goal = Self::cell_at(to);
+        // The endpoints might be right next to a wall
+        let passable = |cell: vec2<i32>| cell == goal || self.is_free(cell);
+        let heuristic =
+            |cell: vec2<i32>| ((cell.x - goal.x).abs() + (cell.y - goal.y).abs()) as f32;
+
+        let mut costs = HashMap::from([(start, 0.0)]);
+        let mut came_from = HashMap::new();
+        let mut queue = BinaryHeap::from([(Reverse(r32(heuristic(start))), start.x, start.y)]);
+        while let Some((_, x, y)) = queue.pop() {
+            let cell = vec2(x, y);
+            if cell == goal {
+                break;
+            }
+            let cost = costs[&cell];
+            for delta in [vec2(0, 1), vec2(1, 0), vec2(0, -1), vec2(-1, 0)] {
+                let next = cell + delta;
+                if !passable(next) {
+                    continue;
+                }
+                let next_cost = cost + 1.0 + dark_weight * self.light_at(next);
+                if costs.get(&next).map_or(true, |&known| next_cost < known) {
+                    costs.insert(next, next_cost);
+                    came_from.insert(next, cell);
+                    let priority = r32(next_cost + heuristic(next));
+                    queue.push((Reverse(priority), next.x, next.y));
+                }
+            }
+        }
+
+        if start != goal && !came_from.contains_key(&goal) {
+            return None;
+        }
+
+        let mut cells = vec![goal];
+        let mut cell = goal;
+        while let Some(&previous) = came_from.get(&cell) {
+            cells.push(previous);
+            cell = previous;
+        }
+        cells.reverse();
+
+        // Keep only the turns
+        let mut points = vec![from];
+        for window in cells.windows(3) {
+            let [a, b, c] = [window[0], window[1], window[2]];
+            if b - a != c - b {
+                points.push(Self::cell_center(b));
+            }
+        }
+        points.push(to);
+        Some(points)
+    }
+}
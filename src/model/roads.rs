@@ -0,0 +1,118 @@
+use super::*;
+
+/// Size of a road grid cell in world units.
+pub const ROAD_CELL_SIZE: f32 = 4.0;
+
+/// Directions to the neighbouring cells: up, right, down, left.
+pub const NEIGHBOURS: [vec2<i32>; 4] = [vec2(0, 1), vec2(1, 0), vec2(0, -1), vec2(-1, 0)];
+
+/// Props that the road tiles are built from.
+const ROAD_PROPS: [&str; 8] = [
+    "road",
+    "road_v",
+    "road_ur",
+    "road_ul",
+    "road_rd",
+    "road_dl",
+    "road_t",
+    "road_cross",
+];
+
+/// Road network on a square grid, where the neighbouring cells are connected.
+#[derive(Debug, Clone, Default)]
+pub struct RoadGrid {
+    cells: HashSet<vec2<i32>>,
+}
+
+impl RoadGrid {
+    /// Collects the road props aligned to the grid.
+    /// Returns the grid and the ids of the props in each cell.
+    pub fn from_props(props: &StructOf<Vec<Prop>>) -> (Self, HashMap<vec2<i32>, usize>) {
+        #[derive(StructQuery)]
+        struct PropRef<'a> {
+            collider: &'a Collider,
+            prop: &'a PropType,
+        }
+
+        let mut grid = Self::default();
+        let mut ids = HashMap::new();
+        for (id, item) in query_prop_ref!(props).iter() {
+            if !ROAD_PROPS.contains(&item.prop.as_str()) {
+                continue;
+            }
+            let cell = Self::cell_at(item.collider.pos());
+            let expected = Self::cell_bounds(cell).map(Coord::as_f32);
+            let actual = item.collider.raw().map(Coord::as_f32);
+            let aligned = (expected.min - actual.min).len() < 0.01 * ROAD_CELL_SIZE
+                && (expected.max - actual.max).len() < 0.01 * ROAD_CELL_SIZE;
+            if aligned {
+                grid.cells.insert(cell);
+                ids.insert(cell, id);
+            }
+        }
+        (grid, ids)
+    }
+
+    pub fn cell_at(position: vec2<Coord>) -> vec2<i32> {
+        position.map(|x| (x.as_f32() / ROAD_CELL_SIZE).floor() as i32)
+    }
+
+    pub fn cell_bounds(cell: vec2<i32>) -> Aabb2<Coord> {
+        let min = cell.map(|x| x as f32 * ROAD_CELL_SIZE);
+        Aabb2::point(min)
+            .extend_positive(vec2::splat(ROAD_CELL_SIZE))
+            .map(Coord::new)
+    }
+
+    pub fn contains(&self, cell: vec2<i32>) -> bool {
+        self.cells.contains(&cell)
+    }
+
+    /// Returns `true` if the cell was not a road before.
+    pub fn insert(&mut self, cell: vec2<i32>) -> bool {
+        self.cells.insert(cell)
+    }
+
+    /// Returns `true` if the cell was a road before.
+    pub fn remove(&mut self, cell: vec2<i32>) -> bool {
+        self.cells.remove(&cell)
+    }
+
+    /// Picks the tile that connects the cell to its neighbours.
+    pub fn tile(&self, cell: vec2<i32>) -> (&'static str, Angle) {
+        let [up, right, down, left] = NEIGHBOURS.map(|delta| self.contains(cell + delta));
+        let straight = Angle::ZERO;
+        match (up, right, down, left) {
+            (true, true, true, true) => ("road_cross", straight),
+            // T-junctions are rotated counter-clockwise from the one missing the top
+            (false, true, true, true) => ("road_t", straight),
+            (true, true, true, false) => ("road_t", Angle::new_degrees(90.0)),
+            (true, true, false, true) => ("road_t", Angle::new_degrees(180.0)),
+            (true, false, true, true) => ("road_t", Angle::new_degrees(270.0)),
+            (true, true, false, false) => ("road_ur", straight),
+            (true, false, false, true) => ("road_ul", straight),
+            (false, true, true, false) => ("road_rd", straight),
+            (false, false, true, true) => ("road_dl", straight),
+            (true, false, _, false) | (false, false, true, false) => ("road_v", straight),
+            _ => ("road", straight),
+        }
+    }
+
+    /// Builds the prop of the road tile in the cell.
+    pub fn prop(&self, cell: vec2<i32>) -> Prop {
+        let (prop, rotation) = self.tile(cell);
+        let mut collider = Collider::new(Self::cell_bounds(cell));
+        collider.rotation = rotation;
+        Prop {
+            collider,
+            prop: prop.to_owned(),
+        }
+    }
+
+    /// Builds the props for all road cells.
+    pub fn props(&self) -> Vec<Prop> {
+        let mut cells: Vec<_> = self.cells.iter().copied().collect();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        cells.into_iter().map(|cell| self.prop(cell)).collect()
+    }
+}
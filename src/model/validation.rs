@@ -0,0 +1,143 @@
+use super::*;
+
+use crate::assets::Props;
+
+use super::logic::{WAYPOINT_DISTANCE_MAX, WAYPOINT_DISTANCE_MIN};
+
+/// Structural problem in a level.
+#[derive(Debug, Clone)]
+pub enum LevelIssue {
+    /// The spawn point is inside the clearance around an obstacle.
+    SpawnBlocked,
+    WaypointOverlapsObstacle {
+        waypoint: usize,
+        obstacle: usize,
+    },
+    WaypointUnreachable {
+        waypoint: usize,
+    },
+    /// There is no other waypoint at a distance the game can choose as the next target.
+    WaypointIsolated {
+        waypoint: usize,
+    },
+    UnknownProp {
+        prop: usize,
+        name: PropType,
+    },
+    PathTooShort {
+        obstacle: usize,
+        points: usize,
+    },
+    LampNeverUp {
+        lamp: usize,
+    },
+}
+
+impl Display for LevelIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SpawnBlocked => write!(f, "spawn point is blocked by an obstacle"),
+            Self::WaypointOverlapsObstacle { waypoint, obstacle } => {
+                write!(f, "waypoint {waypoint} overlaps obstacle {obstacle}")
+            }
+            Self::WaypointUnreachable { waypoint } => {
+                write!(f, "waypoint {waypoint} is unreachable from the spawn point")
+            }
+            Self::WaypointIsolated { waypoint } => write!(
+                f,
+                "waypoint {waypoint} has no other waypoint \
+                {WAYPOINT_DISTANCE_MIN}..={WAYPOINT_DISTANCE_MAX} away"
+            ),
+            Self::UnknownProp { prop, name } => write!(f, "prop {prop} has unknown type {name:?}"),
+            Self::PathTooShort { obstacle, points } => write!(
+                f,
+                "obstacle {obstacle} has a path with {points} point(s), at least 2 are needed"
+            ),
+            Self::LampNeverUp { lamp } => write!(f, "lamp {lamp} has zero up time"),
+        }
+    }
+}
+
+/// Checks the level for structural problems.
+/// `props` is the registry of the prop types known to the game.
+pub fn validate_level(level: &Level, props: &Props) -> Vec<LevelIssue> {
+    #[derive(StructQuery)]
+    struct ObstacleRef<'a> {
+        collider: &'a Collider,
+        path: &'a Option<Path>,
+    }
+
+    #[derive(StructQuery)]
+    struct LampRef<'a> {
+        up_time: &'a Time,
+    }
+
+    #[derive(StructQuery)]
+    struct PropRef<'a> {
+        prop: &'a PropType,
+    }
+
+    let mut issues = Vec::new();
+
+    let obstacles = query_obstacle_ref!(level.obstacles);
+    let nav = NavGrid::new(level, &[&level.obstacles]);
+    if !nav.is_free(NavGrid::cell_at(level.spawn_point)) {
+        issues.push(LevelIssue::SpawnBlocked);
+    }
+    let reachable = nav.reachable(level.spawn_point);
+    let waypoints: Vec<(usize, &Collider)> = level.waypoints.collider.iter().collect();
+    for &(waypoint, collider) in &waypoints {
+        let overlap = obstacles
+            .iter()
+            .find(|(_, item)| item.path.is_none() && item.collider.check(collider));
+        if let Some((obstacle, _)) = overlap {
+            issues.push(LevelIssue::WaypointOverlapsObstacle { waypoint, obstacle });
+        } else {
+            let area = collider.bounding_box();
+            let min = NavGrid::cell_at(area.min);
+            let max = NavGrid::cell_at(area.max);
+            let is_reachable = (min.x..=max.x)
+                .flat_map(|x| (min.y..=max.y).map(move |y| vec2(x, y)))
+                .any(|cell| reachable.contains(&cell));
+            if !is_reachable {
+                issues.push(LevelIssue::WaypointUnreachable { waypoint });
+            }
+        }
+
+        let has_partner = waypoints.iter().any(|&(other, other_collider)| {
+            let distance = (other_collider.pos() - collider.pos()).len().as_f32();
+            other != waypoint && (WAYPOINT_DISTANCE_MIN..=WAYPOINT_DISTANCE_MAX).contains(&distance)
+        });
+        if !has_partner {
+            issues.push(LevelIssue::WaypointIsolated { waypoint });
+        }
+    }
+
+    for (prop, item) in query_prop_ref!(level.props).iter() {
+        if !props.contains(item.prop) {
+            issues.push(LevelIssue::UnknownProp {
+                prop,
+                name: item.prop.clone(),
+            });
+        }
+    }
+
+    for (obstacle, item) in obstacles.iter() {
+        if let Some(path) = item.path {
+            if path.points.len() < 2 {
+                issues.push(LevelIssue::PathTooShort {
+                    obstacle,
+                    points: path.points.len(),
+                });
+            }
+        }
+    }
+
+    for (lamp, item) in query_lamp_ref!(level.lamps).iter() {
+        if *item.up_time <= Time::ZERO {
+            issues.push(LevelIssue::LampNeverUp { lamp });
+        }
+    }
+
+    issues
+}
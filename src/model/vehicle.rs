@@ -0,0 +1,103 @@
+use super::*;
+
+/// How long the obstacles caught in the headlight look towards the player.
+const HEADLIGHT_ALERT_DURATION: f32 = 1.0;
+
+/// Lights and the tradeoffs of the player's vehicle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VehicleProfile {
+    /// Dim light around the vehicle that is always on, in relative coordinates.
+    pub light: Spotlight,
+    /// Forward light toggled by the player, in relative coordinates.
+    pub headlight: Spotlight,
+    /// Added to how visible the player is to the others while the headlight is on.
+    /// It does not hurt the player.
+    pub headlight_visibility: f32,
+}
+
+impl Default for VehicleProfile {
+    fn default() -> Self {
+        Self {
+            light: Spotlight {
+                color: Rgba::opaque(0.8, 0.8, 1.0),
+                angle_range: f32::PI * 2.0,
+                max_distance: Coord::new(3.0),
+                volume: 0.2,
+                intensity: 0.1,
+                ..default()
+            },
+            headlight: Spotlight {
+                color: Rgba::opaque(1.0, 0.95, 0.8),
+                position: vec2(Coord::new(0.3), Coord::ZERO),
+                angle_range: 0.8,
+                max_distance: Coord::new(10.0),
+                volume: 0.3,
+                intensity: 0.6,
+                ..default()
+            },
+            headlight_visibility: 0.15,
+        }
+    }
+}
+
+impl World {
+    /// Lights of the player's vehicle in world coordinates.
+    pub fn player_lights(&self) -> Vec<Spotlight> {
+        let vehicle = &self.player.vehicle;
+        let lights = if self.player.headlight {
+            vec![vehicle.light, vehicle.headlight]
+        } else {
+            vec![vehicle.light]
+        };
+        lights
+            .into_iter()
+            .map(|light| self.player_light(light))
+            .collect()
+    }
+
+    /// Transforms the light of the player's vehicle into world coordinates.
+    fn player_light(&self, light: Spotlight) -> Spotlight {
+        let collider = &self.player.collider;
+        let rotation = collider.rotation.as_radians();
+        Spotlight {
+            position: light.position.rotate(Coord::new(rotation)) + collider.pos(),
+            angle: light.angle + rotation,
+            ..light
+        }
+    }
+
+    /// Alerts the obstacles caught in the headlight.
+    pub(super) fn update_headlight(&mut self) {
+        if !self.player.headlight || self.player.health <= Health::ZERO {
+            return;
+        }
+        let headlight = self.player_light(self.player.vehicle.headlight);
+
+        #[derive(StructQuery)]
+        struct ObstacleRef<'a> {
+            collider: &'a Collider,
+            lights: &'a Vec<Spotlight>,
+            path: &'a Option<Path>,
+            alert: &'a mut Option<Alert>,
+        }
+        let position = self.player.collider.pos();
+        let mut query = query_obstacle_ref!(self.obstacles);
+        let mut iter = query.iter_mut();
+        while let Some((_, item)) = iter.next() {
+            if item.path.is_none() || item.lights.is_empty() {
+                continue;
+            }
+            let delta = item.collider.pos() - headlight.position;
+            let angle =
+                Angle::new_radians(delta.arg().as_f32()) - Angle::new_radians(headlight.angle);
+            let in_cone = angle.as_radians().abs() <= headlight.angle_range / 2.0;
+            if in_cone && delta.len() <= headlight.max_distance {
+                *item.alert = Some(Alert {
+                    position,
+                    time: Time::new(HEADLIGHT_ALERT_DURATION),
+                });
+            }
+        }
+    }
+}
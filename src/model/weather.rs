@@ -0,0 +1,129 @@
+use super::*;
+
+/// Time it takes for the weather to change.
+const WEATHER_TRANSITION: f32 = 5.0;
+const RAIN_DROP_LIFETIME: f32 = 0.4;
+const RAIN_DROP_SPEED: f32 = 12.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Fog,
+}
+
+/// Weather during a part of the run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherWindow {
+    pub weather: Weather,
+    /// Time since the start of the run.
+    #[serde(default)]
+    pub from: Time,
+    /// Lasts until the end of the run if not specified.
+    #[serde(default)]
+    pub until: Option<Time>,
+}
+
+/// How the weather affects the game.
+#[derive(Debug, Clone, Copy)]
+pub struct WeatherEffects {
+    /// Multiplier of the lights' reach.
+    pub light_distance: f32,
+    /// Added to the volumetric light of the spotlights.
+    pub light_volume: f32,
+    /// Multiplier of the player's visibility.
+    pub visibility: f32,
+    /// Multiplier of how fast the car changes its direction.
+    pub grip: f32,
+    /// Rain drops spawned per second.
+    pub rain: f32,
+}
+
+impl Weather {
+    pub fn effects(self) -> WeatherEffects {
+        match self {
+            Self::Clear => WeatherEffects {
+                light_distance: 1.0,
+                light_volume: 0.0,
+                visibility: 1.0,
+                grip: 1.0,
+                rain: 0.0,
+            },
+            Self::Rain => WeatherEffects {
+                light_distance: 0.8,
+                light_volume: 0.1,
+                visibility: 0.8,
+                grip: 0.4,
+                rain: 80.0,
+            },
+            Self::Fog => WeatherEffects {
+                light_distance: 0.6,
+                light_volume: 0.4,
+                visibility: 0.6,
+                grip: 1.0,
+                rain: 0.0,
+            },
+        }
+    }
+}
+
+impl WeatherEffects {
+    fn lerp(self, other: Self, t: f32) -> Self {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        Self {
+            light_distance: lerp(self.light_distance, other.light_distance),
+            light_volume: lerp(self.light_volume, other.light_volume),
+            visibility: lerp(self.visibility, other.visibility),
+            grip: lerp(self.grip, other.grip),
+            rain: lerp(self.rain, other.rain),
+        }
+    }
+
+    /// Applies the effects to the light.
+    pub fn spotlight(&self, light: Spotlight) -> Spotlight {
+        Spotlight {
+            max_distance: light.max_distance * Coord::new(self.light_distance),
+            volume: light.volume + self.light_volume,
+            ..light
+        }
+    }
+}
+
+impl Level {
+    /// Weather at the time since the start of the run.
+    /// The last matching window takes priority.
+    pub fn weather_at(&self, time: Time) -> Weather {
+        self.weather
+            .iter()
+            .rev()
+            .find(|window| window.from <= time && window.until.map_or(true, |until| time < until))
+            .map_or(Weather::Clear, |window| window.weather)
+    }
+}
+
+impl World {
+    pub(super) fn update_weather(&mut self, delta_time: Time) {
+        let target = self.level.weather_at(self.time).effects();
+        let t = (delta_time.as_f32() / WEATHER_TRANSITION).min(1.0);
+        self.weather = self.weather.lerp(target, t);
+
+        // Rain drops around the camera
+        let mut rng = thread_rng();
+        let drops = self.weather.rain * delta_time.as_f32();
+        let drops = drops.floor() as usize + usize::from(rng.gen_bool(drops.fract() as f64));
+        let size = vec2(self.camera.fov, self.camera.fov);
+        let velocity = vec2(-0.3, -1.0).normalize() * RAIN_DROP_SPEED;
+        for _ in 0..drops {
+            let offset = vec2(rng.gen_range(-1.0..=1.0), rng.gen_range(-1.0..=1.0)) * size;
+            self.particles.insert(Particle {
+                position: (self.camera.center + offset).map(Coord::new),
+                velocity: velocity.map(Coord::new),
+                lifetime: Time::new(RAIN_DROP_LIFETIME * rng.gen_range(0.5..=1.0)),
+                radius: Coord::new(0.05),
+                color: Rgba::new(0.6, 0.7, 1.0, 0.5),
+                text: None,
+            });
+        }
+    }
+}
@@ -15,10 +15,23 @@ pub struct World {
     pub camera: Camera2d,
     pub bounced: bool,
     pub hurt_sfx_timeout: Time,
+    /// Present in the endless mode.
+    pub endless: Option<Endless>,
+    pub nav: NavGrid,
+    /// Route from the player to the active waypoint.
+    pub route: Vec<vec2<Coord>>,
+    pub route_timer: Time,
+    /// Whether the route avoids the lit streets.
+    pub prefer_dark_routes: bool,
+    pub weather: WeatherEffects,
+    pub decoys: Vec<Decoy>,
+    /// Time until the next pickup spawns.
+    pub pickup_timer: Time,
 }
 
 impl World {
     pub fn new(assets: &Rc<Assets>, level: Level) -> Self {
+        let level_weather = level.weather_at(Time::ZERO).effects();
         Self {
             assets: assets.clone(),
             time: Time::ZERO,
@@ -31,8 +44,21 @@ impl World {
                     Aabb2::point(level.spawn_point).extend_symmetric(PLAYER_SIZE.map(Coord::new)),
                 ),
                 velocity: vec2::ZERO,
+                noise: 0.0,
+                stamina: 1.0,
+                boosting: false,
+                vehicle: level.vehicle.clone(),
+                headlight: false,
+                hidden: false,
+                inventory: Vec::new(),
+                cloak: Time::ZERO,
+                multiplier: 1.0,
+                streak: 0,
+                grazing: false,
+                near_miss_cooldown: Time::ZERO,
             },
             active_waypoint: 0,
+            nav: NavGrid::new(&level, &[&level.obstacles]),
             level,
             obstacles: StructOf::new(),
             particles: StructOf::new(),
@@ -43,6 +69,13 @@ impl World {
             },
             bounced: false,
             hurt_sfx_timeout: Time::ZERO,
+            endless: None,
+            route: Vec::new(),
+            route_timer: Time::ZERO,
+            prefer_dark_routes: false,
+            weather: level_weather,
+            decoys: Vec::new(),
+            pickup_timer: Time::new(PICKUP_SPAWN_INTERVAL),
         }
     }
 }
@@ -3,14 +3,177 @@ use super::*;
 pub struct RenderCache {
     pub light_geometry: Vec<NormalVertex>,
     pub normal_geometry: ugli::VertexBuffer<NormalVertex>,
+    /// Geometry of the static obstacles in the level by their ids.
+    obstacles: HashMap<usize, ObstacleGeometry>,
+    /// Obstacles whose geometry has to be rebuilt.
+    dirty: HashSet<usize>,
+    /// Light geometry of the props that cast shadows.
+    props: Vec<NormalVertex>,
+    props_dirty: bool,
+    /// Static geometry of the loaded chunks in the endless mode.
+    chunks: HashMap<vec2<i32>, ChunkGeometry>,
+}
+
+struct ChunkGeometry {
+    light: Vec<NormalVertex>,
+    normal: Vec<NormalVertex>,
+}
+
+struct ObstacleGeometry {
+    light: Vec<NormalVertex>,
+    normal: [NormalVertex; 6],
 }
 
 impl RenderCache {
-    pub fn calculate(world: &World, geng: &Geng, assets: &Assets) -> Self {
-        let normal_geometry = world.calculate_normal_geometry(geng, assets);
-        Self {
-            light_geometry: world.calculate_static_light_geometry(),
-            normal_geometry,
+    pub fn calculate(world: &World, geng: &Geng) -> Self {
+        let mut cache = Self {
+            light_geometry: Vec::new(),
+            normal_geometry: ugli::VertexBuffer::new_dynamic(geng.ugli(), Vec::new()),
+            obstacles: HashMap::new(),
+            dirty: HashSet::new(),
+            props: Vec::new(),
+            props_dirty: true,
+            chunks: HashMap::new(),
+        };
+        cache.update(world, geng);
+        cache
+    }
+
+    /// Marks the obstacle's geometry to be rebuilt on the next update.
+    pub fn invalidate(&mut self, obstacle: usize) {
+        self.dirty.insert(obstacle);
+    }
+
+    /// Marks all obstacles starting from the removed one,
+    /// since their ids might have shifted.
+    pub fn invalidate_removed(&mut self, obstacle: usize) {
+        self.dirty
+            .extend(self.obstacles.keys().copied().filter(|&id| id >= obstacle));
+    }
+
+    /// Marks the props' geometry to be rebuilt on the next update.
+    pub fn invalidate_props(&mut self) {
+        self.props_dirty = true;
+    }
+
+    /// Rebuilds the geometry of the dirty and the new obstacles.
+    pub fn update(&mut self, world: &World, geng: &Geng) {
+        #[derive(StructQuery)]
+        struct ObstacleRef<'a> {
+            collider: &'a Collider,
+            path: &'a Option<Path>,
+        }
+        let query = query_obstacle_ref!(world.level.obstacles);
+
+        let mut changed = !self.dirty.is_empty();
+        for id in std::mem::take(&mut self.dirty) {
+            self.obstacles.remove(&id);
+        }
+        let cached = self.obstacles.len();
+        self.obstacles.retain(|&id, _| query.get(id).is_some());
+        changed |= self.obstacles.len() != cached;
+        for (id, item) in query.iter() {
+            if item.path.is_some() || self.obstacles.contains_key(&id) {
+                continue;
+            }
+            self.obstacles.insert(
+                id,
+                ObstacleGeometry {
+                    light: collider_light_geometry(item.collider).collect(),
+                    normal: collider_normal_geometry(item.collider),
+                },
+            );
+            changed = true;
         }
+
+        if std::mem::take(&mut self.props_dirty) {
+            self.props = props_light_geometry(&world.level, &world.assets);
+            changed = true;
+        }
+
+        if changed {
+            self.compose(geng);
+        }
+    }
+
+    /// Adds the static geometry of the chunk of the endless city.
+    pub fn load_chunk(&mut self, chunk: vec2<i32>, level: &Level, assets: &Assets, geng: &Geng) {
+        #[derive(StructQuery)]
+        struct ObstacleRef<'a> {
+            collider: &'a Collider,
+            path: &'a Option<Path>,
+        }
+        let query = query_obstacle_ref!(level.obstacles);
+        let colliders: Vec<&Collider> = query
+            .values()
+            .filter(|item| item.path.is_none())
+            .map(|item| item.collider)
+            .collect();
+        let geometry = ChunkGeometry {
+            light: colliders
+                .iter()
+                .flat_map(|collider| collider_light_geometry(collider))
+                .chain(props_light_geometry(level, assets))
+                .collect(),
+            normal: colliders
+                .iter()
+                .flat_map(|collider| collider_normal_geometry(collider))
+                .collect(),
+        };
+        self.chunks.insert(chunk, geometry);
+        self.compose(geng);
+    }
+
+    pub fn unload_chunk(&mut self, chunk: vec2<i32>, geng: &Geng) {
+        if self.chunks.remove(&chunk).is_some() {
+            self.compose(geng);
+        }
+    }
+
+    /// Combines the cached geometry into the buffers used for rendering.
+    fn compose(&mut self, geng: &Geng) {
+        let mut ids: Vec<usize> = self.obstacles.keys().copied().collect();
+        ids.sort();
+        let obstacles = ids.iter().map(|id| &self.obstacles[id]);
+        self.light_geometry = obstacles
+            .clone()
+            .flat_map(|item| item.light.as_slice().iter().copied())
+            .chain(self.props.as_slice().iter().copied())
+            .chain(
+                self.chunks
+                    .values()
+                    .flat_map(|chunk| chunk.light.as_slice().iter().copied()),
+            )
+            .collect();
+        self.normal_geometry = ugli::VertexBuffer::new_dynamic(
+            geng.ugli(),
+            obstacles
+                .flat_map(|item| item.normal)
+                .chain(
+                    self.chunks
+                        .values()
+                        .flat_map(|chunk| chunk.normal.as_slice().iter().copied()),
+                )
+                .collect(),
+        );
+    }
+}
+
+/// Light geometry of the props that cast shadows.
+fn props_light_geometry(level: &Level, assets: &Assets) -> Vec<NormalVertex> {
+    #[derive(StructQuery)]
+    struct PropRef<'a> {
+        collider: &'a Collider,
+        prop: &'a PropType,
     }
+    let props = &assets.sprites.props;
+    query_prop_ref!(level.props)
+        .values()
+        .filter(|item| {
+            props
+                .config(item.prop)
+                .map_or(false, |config| config.shadows)
+        })
+        .flat_map(|item| collider_light_geometry(item.collider))
+        .collect()
 }
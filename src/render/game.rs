@@ -8,6 +8,15 @@ const PLAYER_BACKGROUND_COLOR: Rgba<f32> = Rgba {
 };
 const PLAYER_RESOLUTION: usize = 50;
 const VISIBILTY_THRESHOLD: f32 = 0.1;
+const ROUTE_WIDTH: f32 = 0.15;
+const ROUTE_COLOR: Rgba<f32> = Rgba {
+    r: 0.0,
+    g: 0.7,
+    b: 0.7,
+    a: 0.4,
+};
+/// How far along the route the arrow points.
+const ROUTE_ARROW_LOOKAHEAD: f32 = 2.0;
 
 pub struct GameRender {
     geng: Geng,
@@ -69,36 +78,58 @@ impl GameRender {
                 .collect();
             let geometry = ugli::VertexBuffer::new_dynamic(self.geng.ugli(), geometry);
             self.lights.render_lights(world, &world.camera, &geometry);
-            let player_light = Spotlight {
-                color: Rgba::opaque(0.8, 0.8, 1.0),
-                position: world.player.collider.pos(),
-                angle_range: f32::PI * 2.0,
-                max_distance: Coord::new(3.0),
-                volume: 0.2,
-                intensity: 0.1,
-                ..default()
-            };
-            self.lights
-                .render_spotlight(&player_light, true, &world.camera, &geometry);
+            for light in world.player_lights() {
+                let light = world.weather.spotlight(light);
+                self.lights
+                    .render_spotlight(&light, true, &world.camera, &geometry);
+            }
             // Finish
             self.lights.finish(framebuffer);
         }
 
+        // Noise around the player
+        let noise_radius = world.noise_radius().as_f32();
+        if noise_radius > 0.0 && world.player.health > Health::ZERO {
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                &world.camera,
+                &draw2d::Ellipse::circle_with_cut(
+                    world.player.collider.pos().map(Coord::as_f32),
+                    noise_radius * 0.95,
+                    noise_radius,
+                    Rgba::new(1.0, 1.0, 1.0, 0.2 * world.player.noise),
+                ),
+            );
+        }
+
+        // Route to the waypoint
+        if world.route.len() >= 2 {
+            let points = world
+                .route
+                .iter()
+                .map(|point| point.map(Coord::as_f32))
+                .collect();
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                &world.camera,
+                &draw2d::Chain::new(Chain::new(points), ROUTE_WIDTH, ROUTE_COLOR, 1),
+            );
+        }
+
         // Waypoint arrow
-        {
+        if let Some(target) = world.level.waypoints.collider.get(world.active_waypoint) {
             let collider = world.player.collider.raw().map(Coord::as_f32);
             let size = collider.size();
             let radius = size.x.max(size.y) * 0.5 * 3.0;
             let aabb = Aabb2::point(collider.center()).extend_uniform(radius);
 
+            // Follow the route around the buildings
             let target = world
-                .level
-                .waypoints
-                .collider
-                .get(world.active_waypoint)
-                .unwrap()
-                .pos()
-                .map(Coord::as_f32);
+                .route
+                .iter()
+                .map(|point| point.map(Coord::as_f32))
+                .find(|point| (*point - collider.center()).len() > ROUTE_ARROW_LOOKAHEAD)
+                .unwrap_or(target.pos().map(Coord::as_f32));
             let rotation = (target - collider.center()).arg();
 
             let shift = vec2::UNIT_X * (world.time.as_f32() * 2.0).sin() * 0.1;
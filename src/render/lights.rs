@@ -84,6 +84,7 @@ impl LightsRender {
         let mut world_framebuffer =
             attach_texture(&mut self.buffers.postprocess_texture, &self.geng);
         let framebuffer_size = world_framebuffer.size();
+        let light = world.global_light();
 
         ugli::draw(
             &mut world_framebuffer,
@@ -93,8 +94,8 @@ impl LightsRender {
             ugli::uniforms! {
                 u_framebuffer_size: framebuffer_size,
                 u_source_texture: &self.buffers.world_texture,
-                u_light_color: world.level.global_light.color,
-                u_light_intensity: world.level.global_light.intensity,
+                u_light_color: light.color,
+                u_light_intensity: light.intensity,
             },
             ugli::DrawParameters {
                 blend_mode: Some(ugli::BlendMode::combined(ugli::ChannelBlendMode {
@@ -122,20 +123,15 @@ impl LightsRender {
             up_time: &'a Time,
             down_time: &'a Time,
         }
+        let power = world.lamps_power();
         let lamps = query_lamp_ref!(world.level.lamps);
         let lamps = lamps.iter().map(|(_, lamp)| {
-            let t = match *lamp.state {
-                LampState::Up(time) => (*lamp.up_time - time).as_f32(),
-                LampState::Down(time) => 1.0 - (*lamp.down_time - time).as_f32(),
-            };
-            let t = t.clamp(0.0, 1.0);
-            let t = 3.0 * t * t - 2.0 * t * t * t; // Smoothstep
-
+            let t = lamp.state.brightness(*lamp.up_time, *lamp.down_time) * power;
             let distance = lamp.light.max_distance * Coord::new(t);
-            let light = Spotlight {
+            let light = world.weather.spotlight(Spotlight {
                 max_distance: distance,
                 ..*lamp.light
-            };
+            });
 
             (light, *lamp.collider)
         });
@@ -147,7 +143,7 @@ impl LightsRender {
                 obstacle
                     .lights
                     .iter()
-                    .map(|(_, &light)| (light, *obstacle.collider))
+                    .map(|(_, &light)| (world.weather.spotlight(light), *obstacle.collider))
             })
             .chain(lamps)
             .map(|(light, collider)| (light, collider.rotation, collider.pos()));
@@ -0,0 +1,193 @@
+use super::*;
+
+/// Size of the minimap relative to the screen height.
+const MINIMAP_SIZE: f32 = 0.25;
+/// Distance from the minimap to the edges of the screen relative to the screen height.
+const MINIMAP_PADDING: f32 = 0.05;
+/// Distance from the player to the edge of the minimap in world units.
+const MINIMAP_RANGE: f32 = 30.0;
+const BACKGROUND_COLOR: Rgba<f32> = Rgba {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.7,
+};
+const OBSTACLE_COLOR: Rgba<f32> = Rgba {
+    r: 0.5,
+    g: 0.5,
+    b: 0.5,
+    a: 1.0,
+};
+const MOVING_OBSTACLE_COLOR: Rgba<f32> = Rgba {
+    r: 1.0,
+    g: 0.3,
+    b: 0.2,
+    a: 1.0,
+};
+const LAMP_COLOR: Rgba<f32> = Rgba {
+    r: 1.0,
+    g: 0.9,
+    b: 0.4,
+    a: 0.3,
+};
+const WAYPOINT_COLOR: Rgba<f32> = Rgba {
+    r: 0.0,
+    g: 0.8,
+    b: 0.8,
+    a: 1.0,
+};
+
+pub struct MinimapRender {
+    geng: Geng,
+}
+
+/// Maps the world around the player onto the minimap.
+struct MinimapView {
+    screen: Aabb2<f32>,
+    center: vec2<f32>,
+    /// Rotation applied to the world, so that the player faces up.
+    rotation: f32,
+}
+
+impl MinimapView {
+    /// Position relative to the minimap center in the range of -1..1 along each axis.
+    fn local(&self, position: vec2<f32>) -> vec2<f32> {
+        ((position - self.center) / MINIMAP_RANGE).rotate(self.rotation)
+    }
+
+    fn to_screen(&self, local: vec2<f32>) -> vec2<f32> {
+        self.screen.center() + local * self.screen.size() / 2.0
+    }
+
+    fn contains(&self, position: vec2<f32>) -> bool {
+        let local = self.local(position);
+        local.x.abs() <= 1.0 && local.y.abs() <= 1.0
+    }
+
+    /// Clamps the points to the edges of the minimap.
+    fn clamped(&self, position: vec2<f32>) -> vec2<f32> {
+        let local = self.local(position);
+        self.to_screen(local.map(|x| x.clamp(-1.0, 1.0)))
+    }
+}
+
+impl MinimapRender {
+    pub fn new(geng: &Geng) -> Self {
+        Self { geng: geng.clone() }
+    }
+
+    /// Draws the minimap in the top right corner.
+    /// With `rotate` the map turns with the player, otherwise north stays up.
+    pub fn draw(&self, world: &World, rotate: bool, framebuffer: &mut ugli::Framebuffer) {
+        let framebuffer_size = framebuffer.size().map(|x| x as f32);
+        let size = framebuffer_size.y * MINIMAP_SIZE;
+        let padding = framebuffer_size.y * MINIMAP_PADDING;
+        let max = framebuffer_size - vec2(padding, padding * 2.0);
+        let heading = world.player.collider.rotation.as_radians();
+        let view = MinimapView {
+            screen: Aabb2::point(max - vec2::splat(size)).extend_positive(vec2::splat(size)),
+            center: world.player.collider.pos().map(Coord::as_f32),
+            rotation: if rotate { f32::PI / 2.0 - heading } else { 0.0 },
+        };
+
+        let camera = &geng::PixelPerfectCamera;
+        let draw2d = self.geng.draw2d();
+        draw2d.draw2d(
+            framebuffer,
+            camera,
+            &draw2d::Quad::new(view.screen, BACKGROUND_COLOR),
+        );
+
+        // Lamps with their current light radius
+        #[derive(StructQuery)]
+        struct LampRef<'a> {
+            collider: &'a Collider,
+            light: &'a Spotlight,
+            state: &'a LampState,
+            up_time: &'a Time,
+            down_time: &'a Time,
+        }
+        let power = world.lamps_power();
+        for lamp in query_lamp_ref!(world.level.lamps).values() {
+            let position = lamp.collider.pos().map(Coord::as_f32);
+            if !view.contains(position) {
+                continue;
+            }
+            let brightness = lamp.state.brightness(*lamp.up_time, *lamp.down_time) * power;
+            let radius = lamp.light.max_distance.as_f32() * brightness / MINIMAP_RANGE * size / 2.0;
+            let center = view.to_screen(view.local(position));
+            draw2d.draw2d(
+                framebuffer,
+                camera,
+                &draw2d::Ellipse::circle(center, radius.max(1.0), LAMP_COLOR),
+            );
+            draw2d.draw2d(
+                framebuffer,
+                camera,
+                &draw2d::Ellipse::circle(center, 2.0, Rgba::YELLOW),
+            );
+        }
+
+        // Obstacle footprints, including the buildings waiting for their difficulty
+        #[derive(StructQuery)]
+        struct ObstacleRef<'a> {
+            collider: &'a Collider,
+            path: &'a Option<Path>,
+        }
+        let waiting = query_obstacle_ref!(world.level.obstacles);
+        let waiting = waiting.values().filter(|obstacle| obstacle.path.is_none());
+        let spawned = query_obstacle_ref!(world.obstacles);
+        for obstacle in waiting.chain(spawned.values()) {
+            let vertices = obstacle.collider.vertices().map(|v| v.map(Coord::as_f32));
+            if !vertices.iter().any(|&v| view.contains(v)) {
+                continue;
+            }
+            let color = if obstacle.path.is_some() {
+                MOVING_OBSTACLE_COLOR
+            } else {
+                OBSTACLE_COLOR
+            };
+            let vertices = vertices.map(|v| view.clamped(v)).to_vec();
+            draw2d.draw2d(framebuffer, camera, &draw2d::Polygon::new(vertices, color));
+        }
+
+        // Route and the delivery target, kept at the edge when out of range
+        if world.route.len() >= 2 {
+            let points = world
+                .route
+                .iter()
+                .map(|point| view.clamped(point.map(Coord::as_f32)))
+                .collect();
+            draw2d.draw2d(
+                framebuffer,
+                camera,
+                &draw2d::Chain::new(Chain::new(points), 2.0, WAYPOINT_COLOR, 1),
+            );
+        }
+        if let Some(target) = world.level.waypoints.collider.get(world.active_waypoint) {
+            let position = view.clamped(target.pos().map(Coord::as_f32));
+            draw2d.draw2d(
+                framebuffer,
+                camera,
+                &draw2d::Ellipse::circle(position, 5.0, WAYPOINT_COLOR),
+            );
+        }
+
+        // Player heading
+        let direction = vec2(1.0, 0.0).rotate(heading + view.rotation);
+        let center = view.screen.center();
+        let length = size * 0.04;
+        draw2d.draw2d(
+            framebuffer,
+            camera,
+            &draw2d::Polygon::new(
+                vec![
+                    center + direction * length,
+                    center + direction.rotate(f32::PI * 0.8) * length,
+                    center + direction.rotate(-f32::PI * 0.8) * length,
+                ],
+                Rgba::WHITE,
+            ),
+        );
+    }
+}
@@ -5,6 +5,7 @@ use super::*;
 mod cache;
 mod game;
 mod lights;
+mod minimap;
 mod player;
 mod util;
 mod world;
@@ -12,6 +13,7 @@ mod world;
 pub use cache::*;
 pub use game::*;
 pub use lights::*;
+pub use minimap::*;
 pub use player::*;
 pub use util::*;
 pub use world::*;
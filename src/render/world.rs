@@ -6,6 +6,8 @@ pub struct WorldRender {
     geng: Geng,
     #[allow(dead_code)]
     assets: Rc<Assets>,
+    /// Props missing from the manifest that have already been reported.
+    missing_props: HashSet<PropType>,
 }
 
 impl WorldRender {
@@ -13,6 +15,7 @@ impl WorldRender {
         Self {
             geng: geng.clone(),
             assets: assets.clone(),
+            missing_props: HashSet::new(),
         }
     }
 
@@ -24,9 +27,11 @@ impl WorldRender {
     ) {
         self.draw_background(world, framebuffer);
         self.draw_props(world, framebuffer, normal_framebuffer);
+        self.draw_hiding_zones(world, framebuffer);
         self.draw_obstacles(world, framebuffer, normal_framebuffer);
         self.draw_lamps(world, framebuffer, normal_framebuffer);
         self.draw_waypoints(world, framebuffer, normal_framebuffer);
+        self.draw_pickups(world, framebuffer);
         if world.player.health > Health::ZERO {
             self.draw_player(world, framebuffer, normal_framebuffer);
         }
@@ -55,7 +60,7 @@ impl WorldRender {
             (
                 ugli::uniforms! {
                     u_model_matrix: matrix,
-                    u_texture: self.assets.sprites.props.bricks.texture(),
+                    u_texture: self.assets.sprites.background.texture(),
                 },
                 world.camera.uniforms(framebuffer.size().map(|x| x as f32)),
             ),
@@ -78,7 +83,12 @@ impl WorldRender {
             prop: &'a PropType,
         }
         for item in query_prop_ref!(world.level.props).values() {
-            let texture = self.assets.sprites.props.get(item.prop).unwrap();
+            let Some(texture) = self.assets.sprites.props.get(item.prop) else {
+                if self.missing_props.insert(item.prop.clone()) {
+                    log::warn!("Prop {:?} is missing from the manifest", item.prop);
+                }
+                continue;
+            };
             self.draw_simple(
                 item.collider,
                 texture,
@@ -89,6 +99,62 @@ impl WorldRender {
         }
     }
 
+    pub fn draw_pickups(&mut self, world: &World, framebuffer: &mut ugli::Framebuffer) {
+        #[derive(StructQuery)]
+        struct PickupRef<'a> {
+            collider: &'a Collider,
+            item: &'a ItemKind,
+        }
+        // Bobbing to stand out from the props
+        let scale = 1.0 + (world.time.as_f32() * 4.0).sin() * 0.1;
+        for item in query_pickup_ref!(world.level.pickups).values() {
+            let size = item.collider.size().map(Coord::as_f32);
+            let radius = size.x.min(size.y) / 2.0 * scale;
+            let position = item.collider.pos().map(Coord::as_f32);
+            let color = item.item.color();
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                &world.camera,
+                &draw2d::Ellipse::circle(position, radius * 0.6, color),
+            );
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                &world.camera,
+                &draw2d::Ellipse::circle_with_cut(position, radius * 0.8, radius, color),
+            );
+        }
+
+        for decoy in &world.decoys {
+            let t = (world.time.as_f32() * 3.0).fract();
+            let radius = 0.3 + t * 1.5;
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                &world.camera,
+                &draw2d::Ellipse::circle_with_cut(
+                    decoy.position.map(Coord::as_f32),
+                    radius * 0.9,
+                    radius,
+                    Rgba {
+                        a: 1.0 - t,
+                        ..ItemKind::Decoy.color()
+                    },
+                ),
+            );
+        }
+    }
+
+    pub fn draw_hiding_zones(&mut self, world: &World, framebuffer: &mut ugli::Framebuffer) {
+        for (_, collider) in world.level.hiding_zones.collider.iter() {
+            draw_collider(
+                collider,
+                Rgba::new(0.0, 0.05, 0.02, 0.4),
+                &self.geng,
+                framebuffer,
+                &world.camera,
+            );
+        }
+    }
+
     pub fn draw_particles(
         &mut self,
         world: &World,
@@ -20,6 +20,22 @@ pub fn report_warn<T, E: Display>(res: Result<T, E>, msg: impl Display) -> Resul
     }
 }
 
+/// Writes the value as json to a temporary file and then moves it in place of the target,
+/// so that the target is never left partially written.
+pub fn write_atomic(path: &std::path::Path, value: &impl Serialize) -> anyhow::Result<()> {
+    let mut name = path.file_name().unwrap_or_default().to_owned();
+    name.push(".tmp");
+    let temp_path = path.with_file_name(name);
+
+    let file = std::fs::File::create(&temp_path)?;
+    let mut writer = std::io::BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut writer, value)?;
+    let file = writer.into_inner()?;
+    file.sync_all()?;
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
 pub fn smooth_step<F: Float>(t: F) -> F {
     let two = F::ONE + F::ONE;
     let three = two + F::ONE;